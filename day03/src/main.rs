@@ -1,3 +1,5 @@
+mod report;
+
 use std::error::Error;
 use std::io::Read;
 
@@ -13,6 +15,30 @@ struct LifeSupportRating {
     co2_scrubber_rating: u32,
 }
 
+#[derive(Debug, PartialEq)]
+struct Column {
+    zeros: u32,
+    ones: u32,
+    gamma: char,
+    epsilon: char,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BitCriteria {
+    MostCommon,
+    LeastCommon,
+}
+
+#[derive(Debug, PartialEq)]
+struct FilterStep {
+    index: usize,
+    zeros: u32,
+    ones: u32,
+    kept: char,
+    before: usize,
+    after: usize,
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let mut buffer = String::new();
 
@@ -20,6 +46,10 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let values: Vec<&str> = buffer.lines().collect();
 
+    if std::env::args().any(|arg| arg == "--report") {
+        report::print_report(&values, 12)?;
+    }
+
     let power_consumption = calculate_power_consumption(&values, 12)?;
     println!(
         "Part 1: {} ({:?})",
@@ -49,78 +79,89 @@ fn calculate_frequency(values: &[&str], n: usize) -> Result<u32, Box<dyn Error>>
     Ok(counter)
 }
 
+fn calculate_columns(values: &[&str], length: usize) -> Result<Vec<Column>, Box<dyn Error>> {
+    let mut columns = vec![];
+
+    let half = (values.len() / 2) as u32;
+    for index in 0..length {
+        let ones = calculate_frequency(values, index)?;
+        let zeros = values.len() as u32 - ones;
+
+        let (gamma, epsilon) = if ones > half { ('1', '0') } else { ('0', '1') };
+
+        columns.push(Column {
+            zeros,
+            ones,
+            gamma,
+            epsilon,
+        });
+    }
+
+    Ok(columns)
+}
+
 fn calculate_power_consumption(
     values: &[&str],
     length: usize,
 ) -> Result<PowerConsumption, Box<dyn Error>> {
-    let mut gamma: Vec<char> = vec![];
-    let mut epsilon: Vec<char> = vec![];
+    let columns = calculate_columns(values, length)?;
 
-    let half = (values.len() / 2) as u32;
-    for index in 0..length {
-        let counter = calculate_frequency(values, index)?;
-
-        if counter > half {
-            gamma.push('1');
-            epsilon.push('0');
-        } else {
-            gamma.push('0');
-            epsilon.push('1');
-        }
-    }
+    let gamma: String = columns.iter().map(|c| c.gamma).collect();
+    let epsilon: String = columns.iter().map(|c| c.epsilon).collect();
 
-    let gamma = u32::from_str_radix(&gamma.iter().collect::<String>(), 2)?;
-    let epsilon = u32::from_str_radix(&epsilon.iter().collect::<String>(), 2)?;
+    let gamma = u32::from_str_radix(&gamma, 2)?;
+    let epsilon = u32::from_str_radix(&epsilon, 2)?;
 
     Ok(PowerConsumption { gamma, epsilon })
 }
 
-fn calculate_life_support_rating(
-    values: &[&str],
+fn filter_rating<'a>(
+    values: &[&'a str],
     length: usize,
-) -> Result<LifeSupportRating, Box<dyn Error>> {
-    let mut oxygen_generator_rating = Vec::from(values);
-    let mut co2_scrubber_rating = Vec::from(values);
+    criteria: BitCriteria,
+) -> Result<(&'a str, Vec<FilterStep>), Box<dyn Error>> {
+    let mut candidates = Vec::from(values);
+    let mut steps = vec![];
 
     for index in 0..length {
-        let oxygen_counter = calculate_frequency(&oxygen_generator_rating, index)?;
-        let oxygen_half = (oxygen_generator_rating.len() as f32) / 2.0;
+        if candidates.len() <= 1 {
+            break;
+        }
 
-        let co2_counter = calculate_frequency(&co2_scrubber_rating, index)?;
-        let co2_half = (co2_scrubber_rating.len() as f32) / 2.0;
+        let ones = calculate_frequency(&candidates, index)?;
+        let zeros = candidates.len() as u32 - ones;
 
-        let most_common_bit = if oxygen_counter >= oxygen_half.ceil() as u32 {
-            '1'
-        } else {
-            '0'
+        let kept = match criteria {
+            BitCriteria::MostCommon if ones >= zeros => '1',
+            BitCriteria::LeastCommon if ones < zeros => '1',
+            _ => '0',
         };
 
-        let least_common_bit = if co2_counter < co2_half.ceil() as u32 {
-            '1'
-        } else {
-            '0'
-        };
+        let before = candidates.len();
+        candidates.retain(|r| r.chars().nth(index) == Some(kept));
+
+        steps.push(FilterStep {
+            index,
+            zeros,
+            ones,
+            kept,
+            before,
+            after: candidates.len(),
+        });
+    }
 
-        if oxygen_generator_rating.len() > 1 {
-            oxygen_generator_rating = oxygen_generator_rating
-                .into_iter()
-                .filter(|r| r.chars().nth(index) == Some(most_common_bit))
-                .collect();
-        }
+    let rating = candidates.pop().ok_or("Unexpected empty values")?;
+    Ok((rating, steps))
+}
 
-        if co2_scrubber_rating.len() > 1 {
-            co2_scrubber_rating = co2_scrubber_rating
-                .into_iter()
-                .filter(|r| r.chars().nth(index) == Some(least_common_bit))
-                .collect();
-        }
-    }
-    let oxygen_generator_rating = oxygen_generator_rating
-        .pop()
-        .ok_or("Unexpected empty values")?;
+fn calculate_life_support_rating(
+    values: &[&str],
+    length: usize,
+) -> Result<LifeSupportRating, Box<dyn Error>> {
+    let (oxygen_generator_rating, _) = filter_rating(values, length, BitCriteria::MostCommon)?;
     let oxygen_generator_rating = u32::from_str_radix(oxygen_generator_rating, 2)?;
 
-    let co2_scrubber_rating = co2_scrubber_rating.pop().ok_or("Unexpected empty values")?;
+    let (co2_scrubber_rating, _) = filter_rating(values, length, BitCriteria::LeastCommon)?;
     let co2_scrubber_rating = u32::from_str_radix(co2_scrubber_rating, 2)?;
 
     Ok(LifeSupportRating {
@@ -160,4 +201,39 @@ mod test_examples {
 
         Ok(())
     }
+
+    #[test]
+    fn test_filter_trace() -> Result<(), Box<dyn Error>> {
+        let values = vec![
+            "00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000",
+            "11001", "00010", "01010",
+        ];
+
+        let columns = calculate_columns(&values, 5)?;
+        assert_eq!(
+            columns[0],
+            Column {
+                zeros: 5,
+                ones: 7,
+                gamma: '1',
+                epsilon: '0',
+            }
+        );
+
+        let (rating, steps) = filter_rating(&values, 5, BitCriteria::MostCommon)?;
+        assert_eq!(rating, "10111");
+        let kept: String = steps.iter().map(|s| s.kept).collect();
+        let after: Vec<usize> = steps.iter().map(|s| s.after).collect();
+        assert_eq!(kept, "10111");
+        assert_eq!(after, vec![7, 4, 3, 2, 1]);
+
+        let (rating, steps) = filter_rating(&values, 5, BitCriteria::LeastCommon)?;
+        assert_eq!(rating, "01010");
+        let kept: String = steps.iter().map(|s| s.kept).collect();
+        let after: Vec<usize> = steps.iter().map(|s| s.after).collect();
+        assert_eq!(kept, "010");
+        assert_eq!(after, vec![5, 2, 1]);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,43 @@
+use super::{calculate_columns, filter_rating, BitCriteria, FilterStep};
+use std::error::Error;
+
+pub fn print_report(values: &[&str], length: usize) -> Result<(), Box<dyn Error>> {
+    println!("Columns ({} values)", values.len());
+    println!(
+        "{:>6} {:>6} {:>6} {:>6} {:>8}",
+        "column", "zeros", "ones", "gamma", "epsilon"
+    );
+    for (index, column) in calculate_columns(values, length)?.iter().enumerate() {
+        println!(
+            "{:>6} {:>6} {:>6} {:>6} {:>8}",
+            index, column.zeros, column.ones, column.gamma, column.epsilon
+        );
+    }
+
+    for (name, criteria) in [
+        ("Oxygen generator", BitCriteria::MostCommon),
+        ("CO2 scrubber", BitCriteria::LeastCommon),
+    ] {
+        let (rating, steps) = filter_rating(values, length, criteria)?;
+        println!();
+        println!("{} rating ({:?})", name, criteria);
+        print_steps(&steps);
+        println!("Result: {} ({})", rating, u32::from_str_radix(rating, 2)?);
+    }
+    println!();
+
+    Ok(())
+}
+
+fn print_steps(steps: &[FilterStep]) {
+    println!(
+        "{:>6} {:>6} {:>6} {:>6} {:>6} {:>6}",
+        "column", "zeros", "ones", "kept", "before", "after"
+    );
+    for step in steps {
+        println!(
+            "{:>6} {:>6} {:>6} {:>6} {:>6} {:>6}",
+            step.index, step.zeros, step.ones, step.kept, step.before, step.after
+        );
+    }
+}
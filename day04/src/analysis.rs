@@ -0,0 +1,161 @@
+use crate::bingo::BingoGame;
+use crate::pattern::Pattern;
+use crate::random::Random;
+use std::error::Error;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct CardStats {
+    /// Games in which the card won in the first round. A first round shared by several
+    /// cards counts as an equal fraction of a win for each, so the first round
+    /// probabilities of all cards add up to 1.
+    pub first: f64,
+    /// Call index (1-based) and score of every win of the card
+    pub calls: Vec<usize>,
+    pub scores: Vec<u32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Analysis {
+    pub runs: usize,
+    pub seed: u64,
+    pub cards: Vec<CardStats>,
+}
+
+/// Plays `runs` games over the numbers of `game.order` shuffled with a generator seeded
+/// by `seed`, recording how every card fares.
+pub fn simulate(
+    game: &BingoGame,
+    patterns: &[Pattern],
+    runs: usize,
+    seed: u64,
+) -> Result<Analysis, Box<dyn Error>> {
+    let mut random = Random::new(seed);
+    let mut cards: Vec<CardStats> = game.cards.iter().map(|_| CardStats::default()).collect();
+    let mut simulation = BingoGame {
+        order: game.order.clone(),
+        cards: game.cards.clone(),
+    };
+
+    for _ in 0..runs {
+        random.shuffle(&mut simulation.order);
+
+        let rounds = simulation.play(patterns)?;
+        for (index, round) in rounds.iter().enumerate() {
+            for result in &round.results {
+                let stats = &mut cards[result.card_index];
+                if index == 0 {
+                    stats.first += 1.0 / round.results.len() as f64;
+                }
+                stats.calls.push(result.called.len());
+                stats.scores.push(result.get_result()?);
+            }
+        }
+    }
+
+    Ok(Analysis { runs, seed, cards })
+}
+
+impl CardStats {
+    pub fn get_first_probability(&self, runs: usize) -> f64 {
+        self.first / runs as f64
+    }
+
+    pub fn get_expected_call(&self) -> Option<f64> {
+        mean(&self.calls)
+    }
+
+    /// Minimum, median, mean and maximum score
+    pub fn get_score_summary(&self) -> Option<(u32, u32, f64, u32)> {
+        let mut scores = self.scores.clone();
+        scores.sort_unstable();
+
+        let min = *scores.first()?;
+        let max = *scores.last()?;
+        let median = scores[scores.len() / 2];
+        let mean = scores.iter().map(|s| *s as f64).sum::<f64>() / scores.len() as f64;
+        Some((min, median, mean, max))
+    }
+}
+
+fn mean(values: &[usize]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<usize>() as f64 / values.len() as f64)
+}
+
+impl Analysis {
+    pub fn print(&self) {
+        println!("{} runs (seed {})", self.runs, self.seed);
+        println!(
+            "{:>5} {:>8} {:>9} {:>8} {:>8} {:>10} {:>8}",
+            "card", "P(first)", "E(call)", "min", "median", "mean", "max"
+        );
+        for (index, card) in self.cards.iter().enumerate() {
+            let call = card.get_expected_call().unwrap_or(f64::NAN);
+            let (min, median, mean, max) = card.get_score_summary().unwrap_or((0, 0, f64::NAN, 0));
+            println!(
+                "{:>5} {:>8.4} {:>9.2} {:>8} {:>8} {:>10.1} {:>8}",
+                index + 1,
+                card.get_first_probability(self.runs),
+                call,
+                min,
+                median,
+                mean,
+                max
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_analysis {
+    use super::*;
+    use crate::bingo::BingoCard;
+
+    #[test]
+    fn test_simulate() -> Result<(), Box<dyn Error>> {
+        let game = BingoGame {
+            order: (1..=6).collect(),
+            cards: vec![
+                BingoCard::new(1, 1, vec![1])?,
+                BingoCard::new(2, 2, vec![2, 3, 4, 5])?,
+            ],
+        };
+        let patterns = Pattern::defaults();
+
+        let analysis = simulate(&game, &patterns, 200, 7)?;
+        assert_eq!(analysis, simulate(&game, &patterns, 200, 7)?);
+
+        let single = &analysis.cards[0];
+        assert_eq!(single.calls.len(), 200);
+        assert_eq!(single.get_score_summary(), Some((0, 0, 0.0, 0)));
+        assert!(single.get_first_probability(200) > 0.5);
+
+        let (min, _, _, max) = analysis.cards[1]
+            .get_score_summary()
+            .ok_or("Missing scores")?;
+        assert!(min >= 2 * 4 && max <= 5 * 7);
+
+        let total: f64 = analysis
+            .cards
+            .iter()
+            .map(|c| c.get_first_probability(200))
+            .sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // identical cards always tie, sharing every win
+        let twins = BingoGame {
+            order: (1..=4).collect(),
+            cards: vec![
+                BingoCard::new(1, 1, vec![3])?,
+                BingoCard::new(1, 1, vec![3])?,
+            ],
+        };
+        let analysis = simulate(&twins, &patterns, 10, 7)?;
+        assert_eq!(analysis.cards[0].get_first_probability(10), 0.5);
+        assert_eq!(analysis.cards[1].get_first_probability(10), 0.5);
+
+        Ok(())
+    }
+}
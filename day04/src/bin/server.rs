@@ -0,0 +1,29 @@
+use day04::bingo::BingoGame;
+use day04::cli::{get_arg, parse_patterns};
+use day04::server::Server;
+use std::error::Error;
+use std::io::Read;
+use std::time::Duration;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut buffer = String::new();
+
+    std::io::stdin().read_to_string(&mut buffer)?;
+
+    let address = get_arg("--address=").unwrap_or_else(|| "127.0.0.1:7878".to_string());
+    let players = get_arg("--players=").map_or(Ok(1), |p| p.parse())?;
+    let interval = get_arg("--interval=").map_or(Ok(1000), |i| i.parse())?;
+
+    let patterns = parse_patterns()?;
+
+    let game = BingoGame::parse(&buffer)?;
+    let server = Server::bind(address, game, patterns)?;
+    println!("Listening on {}", server.local_addr()?);
+
+    match server.run(players, Duration::from_millis(interval))? {
+        Some(winner) => println!("Winner: card {} ({})", winner.card + 1, winner.score),
+        None => println!("No winner"),
+    }
+
+    Ok(())
+}
@@ -1,8 +1,12 @@
+use crate::engine::Engine;
+use crate::pattern::Pattern;
+use std::collections::HashSet;
 use std::error::Error;
+use thiserror::Error;
 
-type Point = u32;
+pub type Point = u32;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BingoCard {
     pub width: usize,
     pub height: usize,
@@ -15,14 +19,59 @@ pub struct BingoGame {
     pub cards: Vec<BingoCard>,
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum CardError {
+    #[error("Empty card")]
+    Empty,
+    #[error("Row {row} has {found} values, expected {expected}")]
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Expected {expected} values for a {width}x{height} card, found {found}")]
+    WrongCount {
+        width: usize,
+        height: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Duplicate value {0}")]
+    Duplicate(Point),
+    #[error("Invalid value")]
+    InvalidValue(#[from] std::num::ParseIntError),
+}
+
 #[derive(Debug)]
 pub struct BingoResult<'a> {
     pub called: &'a [Point],
+    pub card_index: usize,
     pub card: &'a BingoCard,
+    pub pattern: &'a Pattern,
+}
+
+/// Number of cells left on a line at which a card is reported as a near miss
+pub const NEAR_MISS: usize = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BingoEvent<'a> {
+    NumberCalled(Point),
+    /// Card index followed by the x and y of the marked cell
+    CellMarked(usize, usize, usize),
+    /// Card index and the number of cells left on its closest line
+    NearMiss(usize, usize),
+    CardWon(usize, &'a Pattern),
+}
+
+/// All the cards which won on the same call, in the order they appear in the input.
+#[derive(Debug)]
+pub struct BingoRound<'a> {
+    pub called: &'a [Point],
+    pub results: Vec<BingoResult<'a>>,
 }
 
 impl BingoGame {
-    pub fn parse(value: &str, width: usize, height: usize) -> Result<BingoGame, Box<dyn Error>> {
+    pub fn parse(value: &str) -> Result<BingoGame, Box<dyn Error>> {
         let mut lines = value.lines();
 
         let order_line = lines.next().ok_or("Missing order")?;
@@ -35,65 +84,137 @@ impl BingoGame {
         let lines = lines.collect::<Vec<&str>>().join("\n");
         let buffers = lines.split("\n\n");
 
-        for card in buffers {
-            let bingo_card = BingoCard::parse(card, width, height)?;
+        for (index, card) in buffers.filter(|b| !b.trim().is_empty()).enumerate() {
+            let bingo_card =
+                BingoCard::parse(card).map_err(|e| format!("Card {}: {}", index + 1, e))?;
             cards.push(bingo_card);
         }
 
         Ok(BingoGame { order, cards })
     }
 
-    pub fn play(&self) -> Result<Vec<BingoResult>, Box<dyn Error>> {
-        let mut results = vec![];
-        let mut used_cards = vec![];
+    /// Plays the game and groups the winning cards by the call they won on.
+    ///
+    /// Cards completing on the same call are tied. Ties are resolved by input order: the
+    /// first winner of the game is the earliest card of the first round and the last
+    /// winner is the latest card of the final round (see `first_winner` and `last_winner`).
+    pub fn play<'a>(
+        &'a self,
+        patterns: &'a [Pattern],
+    ) -> Result<Vec<BingoRound<'a>>, Box<dyn Error>> {
+        self.play_with(patterns, &mut |_| {})
+    }
 
-        for index in 0..self.order.len() {
-            let called = &self.order[..index];
-            for card in &self.cards {
-                if used_cards.contains(&card) {
-                    continue;
-                }
-                if card.check(called) {
-                    used_cards.push(card);
-                    let result = BingoResult { called, card };
-                    results.push(result);
+    /// Same as `play`, additionally passing every event of the game to `observer` as it
+    /// happens.
+    pub fn play_with<'a>(
+        &'a self,
+        patterns: &'a [Pattern],
+        observer: &mut dyn FnMut(&BingoEvent<'a>),
+    ) -> Result<Vec<BingoRound<'a>>, Box<dyn Error>> {
+        let mut rounds = vec![];
+        let mut engine = Engine::new(&self.cards, patterns);
+
+        for (index, number) in self.order.iter().enumerate() {
+            let called = &self.order[..=index];
+            let mut results = vec![];
+            observer(&BingoEvent::NumberCalled(*number));
+
+            for mark in engine.call(*number) {
+                let card = &self.cards[mark.card];
+                let x = mark.cell % card.width;
+                let y = mark.cell / card.width;
+                observer(&BingoEvent::CellMarked(mark.card, x, y));
+
+                match mark.pattern {
+                    Some(pattern) => {
+                        let pattern = &patterns[pattern];
+                        debug_assert_eq!(card.check(called, patterns), Some(pattern));
+                        observer(&BingoEvent::CardWon(mark.card, pattern));
+
+                        results.push(BingoResult {
+                            called,
+                            card_index: mark.card,
+                            card,
+                            pattern,
+                        });
+                    }
+                    None if mark.remaining <= NEAR_MISS => {
+                        observer(&BingoEvent::NearMiss(mark.card, mark.remaining));
+                    }
+                    None => {}
                 }
             }
+
+            if !results.is_empty() {
+                rounds.push(BingoRound { called, results });
+            }
         }
 
-        Ok(results)
+        Ok(rounds)
     }
 }
 
 impl BingoCard {
-    pub fn parse(value: &str, width: usize, height: usize) -> Result<BingoCard, Box<dyn Error>> {
-        let mut values = vec![];
-        for line in value.lines() {
-            for point in line.split(' ') {
-                if !point.is_empty() {
-                    values.push(point.parse()?);
-                }
+    /// Parses a card from whitespace separated rows, inferring its dimensions from the
+    /// number of rows and the length of the first row.
+    pub fn parse(value: &str) -> Result<BingoCard, CardError> {
+        let mut rows = vec![];
+        for line in value.lines().filter(|l| !l.trim().is_empty()) {
+            let mut row = vec![];
+            for point in line.split_whitespace() {
+                row.push(point.parse()?);
+            }
+            rows.push(row);
+        }
+
+        let width = rows.first().ok_or(CardError::Empty)?.len();
+        for (row, values) in rows.iter().enumerate() {
+            if values.len() != width {
+                return Err(CardError::RaggedRow {
+                    row: row + 1,
+                    expected: width,
+                    found: values.len(),
+                });
+            }
+        }
+
+        BingoCard::new(width, rows.len(), rows.concat())
+    }
+
+    pub fn new(width: usize, height: usize, values: Vec<Point>) -> Result<BingoCard, CardError> {
+        if width == 0 || height == 0 {
+            return Err(CardError::Empty);
+        }
+        if values.len() != width * height {
+            return Err(CardError::WrongCount {
+                width,
+                height,
+                expected: width * height,
+                found: values.len(),
+            });
+        }
+
+        let mut seen = HashSet::new();
+        for value in &values {
+            if !seen.insert(value) {
+                return Err(CardError::Duplicate(*value));
             }
         }
 
         Ok(BingoCard {
-            values,
             width,
             height,
+            values,
         })
     }
 
     pub fn get_uncalled(&self, values: &[Point]) -> Vec<&Point> {
-        let mut uncalled = vec![];
-        for point in &self.values {
-            if !values.contains(point) {
-                uncalled.push(point);
-            }
-        }
-        uncalled
+        let values: HashSet<&Point> = values.iter().collect();
+        self.values.iter().filter(|p| !values.contains(p)).collect()
     }
 
-    fn get_value(&self, x: usize, y: usize) -> Option<&Point> {
+    pub fn get_value(&self, x: usize, y: usize) -> Option<&Point> {
         if x >= self.width || y >= self.height {
             return None;
         }
@@ -102,41 +223,16 @@ impl BingoCard {
         Some(&self.values[index])
     }
 
-    // This could probably done in a muchhhh nicer way
-    pub fn check(&self, values: &[Point]) -> bool {
-        // Columns
-        for x in 0..self.width {
-            let mut found = true;
-            for y in 0..self.height {
-                if let Some(value) = self.get_value(x, y) {
-                    if !values.contains(value) {
-                        found = false;
-                        break;
-                    }
-                }
-            }
-            if found {
-                return true;
-            }
-        }
-
-        // Rows
-        for y in 0..self.height {
-            let mut found = true;
-            for x in 0..self.width {
-                if let Some(value) = self.get_value(x, y) {
-                    if !values.contains(value) {
-                        found = false;
-                        break;
-                    }
-                }
-            }
-            if found {
-                return true;
-            }
-        }
+    /// Returns the first of `patterns` completed by the called `values`, if any.
+    pub fn check<'a>(&self, values: &[Point], patterns: &'a [Pattern]) -> Option<&'a Pattern> {
+        let values: HashSet<&Point> = values.iter().collect();
 
-        false
+        patterns.iter().find(|pattern| {
+            pattern
+                .lines(self.width, self.height)
+                .iter()
+                .any(|line| line.iter().all(|cell| values.contains(&self.values[*cell])))
+        })
     }
 }
 
@@ -154,6 +250,38 @@ impl<'a> BingoResult<'a> {
     pub fn get_uncalled_sum(&self) -> u32 {
         self.card.get_uncalled(self.called).into_iter().sum()
     }
+
+    /// Returns the cells of the line which won the card
+    pub fn get_winning_cells(&self) -> Vec<usize> {
+        let called: HashSet<&Point> = self.called.iter().collect();
+
+        self.pattern
+            .lines(self.card.width, self.card.height)
+            .into_iter()
+            .find(|line| {
+                line.iter()
+                    .all(|cell| called.contains(&self.card.values[*cell]))
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl<'a> BingoRound<'a> {
+    pub fn get_last_called(&self) -> Option<&Point> {
+        self.called.last()
+    }
+
+    pub fn is_tie(&self) -> bool {
+        self.results.len() > 1
+    }
+}
+
+pub fn first_winner<'a, 'b>(rounds: &'b [BingoRound<'a>]) -> Option<&'b BingoResult<'a>> {
+    rounds.first().and_then(|r| r.results.first())
+}
+
+pub fn last_winner<'a, 'b>(rounds: &'b [BingoRound<'a>]) -> Option<&'b BingoResult<'a>> {
+    rounds.last().and_then(|r| r.results.last())
 }
 
 #[cfg(test)]
@@ -185,7 +313,7 @@ mod test_bingo_game {
         ]
         .join("\n");
 
-        let game = BingoGame::parse(&example, 5, 5)?;
+        let game = BingoGame::parse(&example)?;
 
         let expected_order = vec![
             7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19,
@@ -193,18 +321,122 @@ mod test_bingo_game {
         ];
         assert_eq!(expected_order, game.order);
 
-        let results = game.play()?;
-        let first_result = results.first().ok_or("Missing first result")?;
+        let patterns = Pattern::defaults();
+        let rounds = game.play(&patterns)?;
+        let first_result = first_winner(&rounds).ok_or("Missing first result")?;
 
         assert_eq!(first_result.get_last_called(), Some(&24));
         assert_eq!(first_result.get_uncalled_sum(), 188);
         assert_eq!(first_result.get_result()?, 4512);
+        assert_eq!(first_result.pattern, &Pattern::Row);
 
-        let last_result = results.last().ok_or("Missing last result")?;
+        let last_result = last_winner(&rounds).ok_or("Missing last result")?;
         assert_eq!(last_result.get_last_called(), Some(&13));
         assert_eq!(last_result.get_uncalled_sum(), 148);
         assert_eq!(last_result.get_result()?, 1924);
 
         Ok(())
     }
+
+    #[test]
+    fn test_card_dimensions() -> Result<(), Box<dyn Error>> {
+        let example = ["3,4,1,5", "", "1 2 3", "4 5 6", "", "3 4", "5 1"].join("\n");
+
+        let game = BingoGame::parse(&example)?;
+        assert_eq!(game.cards[0].width, 3);
+        assert_eq!(game.cards[0].height, 2);
+        assert_eq!(game.cards[1].width, 2);
+        assert_eq!(game.cards[1].height, 2);
+
+        let patterns = Pattern::defaults();
+        let rounds = game.play(&patterns)?;
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0].results[0].card, &game.cards[1]);
+        assert_eq!(rounds[0].results[0].get_result()?, 4 * 6);
+        assert_eq!(rounds[1].results[0].card, &game.cards[0]);
+        assert_eq!(rounds[1].results[0].get_result()?, 13);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ties() -> Result<(), Box<dyn Error>> {
+        let example = [
+            "1,2,3,4", "", "1 2", "5 6", "", "7 8", "2 1", "", "3 4", "9 10",
+        ]
+        .join("\n");
+
+        let game = BingoGame::parse(&example)?;
+        let patterns = Pattern::defaults();
+        let rounds = game.play(&patterns)?;
+
+        assert_eq!(rounds.len(), 2);
+        assert!(rounds[0].is_tie());
+        assert_eq!(rounds[0].get_last_called(), Some(&2));
+        let tied: Vec<usize> = rounds[0].results.iter().map(|r| r.card_index).collect();
+        assert_eq!(tied, vec![0, 1]);
+        assert!(!rounds[1].is_tie());
+
+        let first = first_winner(&rounds).ok_or("Missing first result")?;
+        assert_eq!(first.card_index, 0);
+        assert_eq!(first.get_result()?, 2 * 11);
+
+        let last = last_winner(&rounds).ok_or("Missing last result")?;
+        assert_eq!(last.card_index, 2);
+        assert_eq!(last.get_result()?, 4 * 19);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_events() -> Result<(), Box<dyn Error>> {
+        let game = BingoGame::parse("9,1,2,3\n\n1 2 3\n4 5 6")?;
+        let patterns = Pattern::defaults();
+
+        let mut events = vec![];
+        let rounds = game.play_with(&patterns, &mut |e| events.push(e.clone()))?;
+
+        assert_eq!(rounds.len(), 1);
+        assert_eq!(
+            events,
+            vec![
+                BingoEvent::NumberCalled(9),
+                BingoEvent::NumberCalled(1),
+                BingoEvent::CellMarked(0, 0, 0),
+                BingoEvent::NearMiss(0, 1),
+                BingoEvent::NumberCalled(2),
+                BingoEvent::CellMarked(0, 1, 0),
+                BingoEvent::NearMiss(0, 1),
+                BingoEvent::NumberCalled(3),
+                BingoEvent::CellMarked(0, 2, 0),
+                BingoEvent::CardWon(0, &Pattern::Row),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_cards() {
+        assert_eq!(BingoCard::parse(""), Err(CardError::Empty));
+        assert_eq!(
+            BingoCard::parse("1 2 3\n4 5"),
+            Err(CardError::RaggedRow {
+                row: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(BingoCard::parse("1 2\n3 1"), Err(CardError::Duplicate(1)));
+        assert_eq!(
+            BingoCard::new(2, 2, vec![1, 2, 3]),
+            Err(CardError::WrongCount {
+                width: 2,
+                height: 2,
+                expected: 4,
+                found: 3
+            })
+        );
+        assert!(BingoGame::parse("1,2\n\n1 x").is_err());
+    }
 }
@@ -0,0 +1,33 @@
+use crate::pattern::Pattern;
+use std::error::Error;
+
+pub fn has_flag(flag: &str) -> bool {
+    std::env::args().any(|a| a == flag)
+}
+
+pub fn get_arg(prefix: &str) -> Option<String> {
+    std::env::args().find_map(|a| a.strip_prefix(prefix).map(String::from))
+}
+
+/// Reads the active patterns from `--patterns=row,column,...` and `--masks=<file>`,
+/// falling back to rows and columns when neither is given.
+pub fn parse_patterns() -> Result<Vec<Pattern>, Box<dyn Error>> {
+    let mut patterns = vec![];
+    let mut masks = vec![];
+
+    for arg in std::env::args().skip(1) {
+        if let Some(names) = arg.strip_prefix("--patterns=") {
+            for name in names.split(',') {
+                patterns.push(name.parse()?);
+            }
+        } else if let Some(path) = arg.strip_prefix("--masks=") {
+            masks.extend(Pattern::parse_masks(&std::fs::read_to_string(path)?)?);
+        }
+    }
+
+    if patterns.is_empty() && masks.is_empty() {
+        return Ok(Pattern::defaults());
+    }
+    patterns.extend(masks);
+    Ok(patterns)
+}
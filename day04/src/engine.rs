@@ -0,0 +1,185 @@
+use crate::bingo::{BingoCard, Point};
+use crate::pattern::Pattern;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq)]
+pub struct Mark {
+    pub card: usize,
+    pub cell: usize,
+    /// Fewest cells left to mark on any line of the card after this mark
+    pub remaining: usize,
+    /// The first pattern completed by this mark, if the card won
+    pub pattern: Option<usize>,
+}
+
+/// Incremental marking state for a set of cards.
+///
+/// Every card is broken down into the lines of each active pattern which keep a count of
+/// how many of their cells have been marked, so calling a number only touches the cells
+/// that actually contain it.
+#[derive(Debug)]
+pub struct Engine {
+    positions: HashMap<Point, Vec<(usize, usize)>>,
+    cell_lines: Vec<Vec<Vec<usize>>>,
+    line_lengths: Vec<Vec<usize>>,
+    line_patterns: Vec<Vec<usize>>,
+    hits: Vec<Vec<usize>>,
+    /// Fewest cells left on any line of each card, which only ever shrinks
+    closest: Vec<usize>,
+    marked: Vec<Vec<bool>>,
+    won: Vec<bool>,
+}
+
+impl Engine {
+    pub fn new(cards: &[BingoCard], patterns: &[Pattern]) -> Engine {
+        let mut positions: HashMap<Point, Vec<(usize, usize)>> = HashMap::new();
+        let mut cell_lines = vec![];
+        let mut line_lengths = vec![];
+        let mut line_patterns = vec![];
+
+        for (card_index, card) in cards.iter().enumerate() {
+            for (cell, value) in card.values.iter().enumerate() {
+                positions
+                    .entry(*value)
+                    .or_default()
+                    .push((card_index, cell));
+            }
+
+            let mut lines = vec![vec![]; card.values.len()];
+            let mut lengths = vec![];
+            let mut line_pattern = vec![];
+            for (pattern, cells) in patterns.iter().enumerate().flat_map(|(i, p)| {
+                p.lines(card.width, card.height)
+                    .into_iter()
+                    .map(move |l| (i, l))
+            }) {
+                for cell in &cells {
+                    lines[*cell].push(lengths.len());
+                }
+                lengths.push(cells.len());
+                line_pattern.push(pattern);
+            }
+            cell_lines.push(lines);
+            line_lengths.push(lengths);
+            line_patterns.push(line_pattern);
+        }
+
+        Engine {
+            positions,
+            hits: line_lengths.iter().map(|l| vec![0; l.len()]).collect(),
+            closest: line_lengths
+                .iter()
+                .map(|l| l.iter().copied().min().unwrap_or(usize::MAX))
+                .collect(),
+            marked: cards.iter().map(|c| vec![false; c.values.len()]).collect(),
+            won: vec![false; cards.len()],
+            cell_lines,
+            line_lengths,
+            line_patterns,
+        }
+    }
+
+    /// Marks `number` on every card that contains it which has not already won, returning
+    /// the new marks in card order.
+    pub fn call(&mut self, number: Point) -> Vec<Mark> {
+        let mut marks = vec![];
+        let positions = match self.positions.get(&number) {
+            Some(positions) => positions,
+            None => return marks,
+        };
+
+        for &(card, cell) in positions {
+            if self.won[card] || self.marked[card][cell] {
+                continue;
+            }
+            self.marked[card][cell] = true;
+
+            let mut pattern = None;
+            for &line in &self.cell_lines[card][cell] {
+                self.hits[card][line] += 1;
+
+                let left = self.line_lengths[card][line] - self.hits[card][line];
+                if left == 0 && pattern.is_none() {
+                    pattern = Some(self.line_patterns[card][line]);
+                }
+                self.closest[card] = self.closest[card].min(left);
+            }
+
+            self.won[card] = pattern.is_some();
+            marks.push(Mark {
+                card,
+                cell,
+                remaining: self.closest[card],
+                pattern,
+            });
+        }
+
+        marks
+    }
+}
+
+#[cfg(test)]
+mod test_engine {
+    use super::*;
+
+    fn get_wins(marks: Vec<Mark>) -> Vec<(usize, usize)> {
+        marks
+            .into_iter()
+            .filter_map(|m| m.pattern.map(|p| (m.card, p)))
+            .collect()
+    }
+
+    #[test]
+    fn test_call() {
+        let cards = vec![
+            BingoCard {
+                width: 3,
+                height: 2,
+                values: vec![1, 2, 3, 4, 5, 6],
+            },
+            BingoCard {
+                width: 2,
+                height: 2,
+                values: vec![3, 1, 7, 5],
+            },
+        ];
+        let mut engine = Engine::new(&cards, &Pattern::defaults());
+
+        assert_eq!(engine.call(1).len(), 2);
+        assert_eq!(engine.call(1), vec![]);
+        assert_eq!(get_wins(engine.call(8)), vec![]);
+        assert_eq!(get_wins(engine.call(4)), vec![(0, 1)]);
+        assert_eq!(get_wins(engine.call(5)), vec![(1, 1)]);
+        assert_eq!(get_wins(engine.call(3)), vec![]);
+    }
+
+    #[test]
+    fn test_call_patterns() {
+        let cards = vec![BingoCard {
+            width: 3,
+            height: 3,
+            values: vec![1, 2, 3, 4, 5, 6, 7, 8, 9],
+        }];
+        let patterns = vec![Pattern::Row, Pattern::Diagonal];
+        let mut engine = Engine::new(&cards, &patterns);
+
+        assert_eq!(engine.call(3)[0].remaining, 2);
+        assert_eq!(engine.call(7)[0].remaining, 1);
+        assert_eq!(get_wins(engine.call(5)), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_call_closest_line() {
+        let cards = vec![BingoCard {
+            width: 3,
+            height: 3,
+            values: vec![1, 2, 3, 4, 5, 6, 7, 8, 9],
+        }];
+        let mut engine = Engine::new(&cards, &[Pattern::Row]);
+
+        assert_eq!(engine.call(1)[0].remaining, 2);
+        assert_eq!(engine.call(2)[0].remaining, 1);
+        // the first row stays the closest line when a cell of another row is marked
+        assert_eq!(engine.call(9)[0].remaining, 1);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod analysis;
+pub mod bingo;
+pub mod cli;
+mod engine;
+pub mod pattern;
+mod random;
+pub mod render;
+pub mod search;
+pub mod server;
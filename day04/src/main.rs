@@ -1,23 +1,97 @@
-mod bingo;
-
-use bingo::BingoGame;
+use day04::analysis;
+use day04::bingo::{first_winner, last_winner, BingoGame, BingoResult, BingoRound};
+use day04::cli::{get_arg, has_flag, parse_patterns};
+use day04::pattern::Pattern;
+use day04::render::{self, Style};
+use day04::search::{self, Goal};
 use std::error::Error;
 use std::io::Read;
+use std::time::Duration;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let mut buffer = String::new();
 
     std::io::stdin().read_to_string(&mut buffer)?;
 
-    let game = BingoGame::parse(&buffer, 5, 5)?;
-    let results = game.play()?;
+    let patterns = parse_patterns()?;
+
+    let game = BingoGame::parse(&buffer)?;
+
+    if let Some(goal) = get_arg("--design=") {
+        return design(&game, &patterns, goal.parse()?);
+    }
+    if let Some(runs) = get_arg("--simulate=") {
+        let seed = get_arg("--seed=").map_or(Ok(0), |s| s.parse())?;
+        analysis::simulate(&game, &patterns, runs.parse()?, seed)?.print();
+        return Ok(());
+    }
+
+    let style = if has_flag("--plain") {
+        Style::Plain
+    } else {
+        Style::Ansi
+    };
+    if has_flag("--step") || get_arg("--step=").is_some() {
+        let delay = get_arg("--step=").map_or(Ok(500), |d| d.parse())?;
+        return render::step_through(&game, &patterns, style, Duration::from_millis(delay));
+    }
+
+    let rounds = if has_flag("--events") {
+        game.play_with(&patterns, &mut |event| println!("{:?}", event))?
+    } else {
+        game.play(&patterns)?
+    };
 
-    if let Some(result) = results.first() {
-        println!("Part 1: {}", result.get_result()?);
+    if let Some(result) = first_winner(&rounds) {
+        println!("Part 1: {} ({})", result.get_result()?, result.pattern);
+        print_ties(&rounds[0])?;
+        if has_flag("--render") {
+            print_result(result, style);
+        }
     }
-    if let Some(result) = results.last() {
-        println!("Part 2: {}", result.get_result()?);
+    if let Some(result) = last_winner(&rounds) {
+        println!("Part 2: {} ({})", result.get_result()?, result.pattern);
+        print_ties(&rounds[rounds.len() - 1])?;
+        if has_flag("--render") {
+            print_result(result, style);
+        }
     }
 
     Ok(())
 }
+
+/// Prints a call order which achieves `goal` for the cards of `game`.
+fn design(game: &BingoGame, patterns: &[Pattern], goal: Goal) -> Result<(), Box<dyn Error>> {
+    let order = search::find_order(&game.cards, patterns, goal).ok_or("No call order found")?;
+    if !search::verify(&game.cards, patterns, goal, &order)? {
+        return Err("Call order failed validation".into());
+    }
+
+    let order: Vec<String> = order.iter().map(|p| p.to_string()).collect();
+    println!("{}", order.join(","));
+    Ok(())
+}
+
+fn print_result(result: &BingoResult, style: Style) {
+    let winning = result.get_winning_cells();
+    print!(
+        "{}",
+        render::render_card(result.card, result.called, &winning, style)
+    );
+}
+
+fn print_ties(round: &BingoRound) -> Result<(), Box<dyn Error>> {
+    if round.is_tie() {
+        let called = round.get_last_called().ok_or("No last called")?;
+        println!("  tied on call {}:", called);
+        for result in &round.results {
+            println!(
+                "    card {}: {} ({})",
+                result.card_index + 1,
+                result.get_result()?,
+                result.pattern
+            );
+        }
+    }
+    Ok(())
+}
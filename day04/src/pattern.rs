@@ -0,0 +1,221 @@
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Row,
+    Column,
+    Diagonal,
+    FourCorners,
+    X,
+    Blackout,
+    Mask {
+        name: String,
+        width: usize,
+        height: usize,
+        cells: Vec<bool>,
+    },
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PatternError {
+    #[error("Unknown pattern: {0}")]
+    Unknown(String),
+    #[error("Invalid mask cell: {0}")]
+    InvalidCell(char),
+    #[error("Mask {0} has rows of different lengths")]
+    RaggedMask(String),
+    #[error("Mask {0} has no cells set")]
+    EmptyMask(String),
+}
+
+impl Pattern {
+    /// The patterns of a regular game of bingo
+    pub fn defaults() -> Vec<Pattern> {
+        vec![Pattern::Row, Pattern::Column]
+    }
+
+    /// Returns the groups of cell indexes which win with this pattern on a card of the
+    /// given size once every cell in the group is marked. Patterns which do not fit the
+    /// card (diagonals on non-square cards, masks of a different size) have no lines.
+    pub fn lines(&self, width: usize, height: usize) -> Vec<Vec<usize>> {
+        let index = |x: usize, y: usize| x + (width * y);
+        let square = width == height;
+
+        match self {
+            Pattern::Row => (0..height)
+                .map(|y| (0..width).map(|x| index(x, y)).collect())
+                .collect(),
+            Pattern::Column => (0..width)
+                .map(|x| (0..height).map(|y| index(x, y)).collect())
+                .collect(),
+            Pattern::Diagonal if square => vec![
+                (0..width).map(|i| index(i, i)).collect(),
+                (0..width).map(|i| index(width - 1 - i, i)).collect(),
+            ],
+            Pattern::X if square => {
+                let mut cells: Vec<usize> = (0..width)
+                    .flat_map(|i| [index(i, i), index(width - 1 - i, i)])
+                    .collect();
+                cells.sort_unstable();
+                cells.dedup();
+                vec![cells]
+            }
+            Pattern::FourCorners => {
+                let mut cells = vec![
+                    index(0, 0),
+                    index(width - 1, 0),
+                    index(0, height - 1),
+                    index(width - 1, height - 1),
+                ];
+                cells.sort_unstable();
+                cells.dedup();
+                vec![cells]
+            }
+            Pattern::Blackout => vec![(0..width * height).collect()],
+            Pattern::Mask {
+                width: mask_width,
+                height: mask_height,
+                cells,
+                ..
+            } if *mask_width == width && *mask_height == height => vec![cells
+                .iter()
+                .enumerate()
+                .filter(|(_, set)| **set)
+                .map(|(i, _)| i)
+                .collect()],
+            _ => vec![],
+        }
+    }
+
+    /// Parses user-defined masks separated by blank lines. Each mask is a grid of `#` (cell
+    /// required) and `.` (cell ignored), optionally preceded by a `name:` line.
+    pub fn parse_masks(value: &str) -> Result<Vec<Pattern>, PatternError> {
+        let mut masks = vec![];
+        let mut lines = value.lines().map(str::trim).peekable();
+
+        while lines.peek().is_some() {
+            let block: Vec<&str> = lines.by_ref().take_while(|l| !l.is_empty()).collect();
+            if block.is_empty() {
+                continue;
+            }
+
+            let (name, rows) = match block[0].strip_suffix(':') {
+                Some(name) => (name.to_string(), &block[1..]),
+                None => (format!("mask {}", masks.len() + 1), &block[..]),
+            };
+
+            let mut cells = vec![];
+            let mut widths = vec![];
+            for row in rows {
+                let mut width = 0;
+                for character in row.chars().filter(|c| !c.is_whitespace()) {
+                    match character {
+                        '#' => cells.push(true),
+                        '.' => cells.push(false),
+                        c => return Err(PatternError::InvalidCell(c)),
+                    }
+                    width += 1;
+                }
+                widths.push(width);
+            }
+
+            let height = rows.len();
+            let width = widths.first().copied().unwrap_or(0);
+            if widths.iter().any(|w| *w != width) {
+                return Err(PatternError::RaggedMask(name));
+            }
+            if !cells.contains(&true) {
+                return Err(PatternError::EmptyMask(name));
+            }
+
+            masks.push(Pattern::Mask {
+                name,
+                width,
+                height,
+                cells,
+            });
+        }
+
+        Ok(masks)
+    }
+}
+
+impl std::str::FromStr for Pattern {
+    type Err = PatternError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value {
+            "row" => Pattern::Row,
+            "column" => Pattern::Column,
+            "diagonal" => Pattern::Diagonal,
+            "corners" => Pattern::FourCorners,
+            "x" => Pattern::X,
+            "blackout" => Pattern::Blackout,
+            _ => return Err(PatternError::Unknown(value.to_string())),
+        })
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pattern::Row => write!(f, "row"),
+            Pattern::Column => write!(f, "column"),
+            Pattern::Diagonal => write!(f, "diagonal"),
+            Pattern::FourCorners => write!(f, "corners"),
+            Pattern::X => write!(f, "x"),
+            Pattern::Blackout => write!(f, "blackout"),
+            Pattern::Mask { name, .. } => write!(f, "{}", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_pattern {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(Pattern::Row.lines(3, 2), vec![vec![0, 1, 2], vec![3, 4, 5]]);
+        assert_eq!(
+            Pattern::Column.lines(3, 2),
+            vec![vec![0, 3], vec![1, 4], vec![2, 5]]
+        );
+        assert_eq!(
+            Pattern::Diagonal.lines(3, 3),
+            vec![vec![0, 4, 8], vec![2, 4, 6]]
+        );
+        assert_eq!(Pattern::Diagonal.lines(3, 2), Vec::<Vec<usize>>::new());
+        assert_eq!(Pattern::X.lines(3, 3), vec![vec![0, 2, 4, 6, 8]]);
+        assert_eq!(Pattern::FourCorners.lines(3, 2), vec![vec![0, 2, 3, 5]]);
+        assert_eq!(Pattern::Blackout.lines(2, 2), vec![vec![0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn test_parse_masks() -> Result<(), PatternError> {
+        let masks = Pattern::parse_masks("plus:\n.#.\n###\n.#.\n\n#.\n.#\n")?;
+
+        assert_eq!(masks.len(), 2);
+        assert_eq!(masks[0].to_string(), "plus");
+        assert_eq!(masks[0].lines(3, 3), vec![vec![1, 3, 4, 5, 7]]);
+        assert_eq!(masks[0].lines(2, 2), Vec::<Vec<usize>>::new());
+        assert_eq!(masks[1].to_string(), "mask 2");
+        assert_eq!(masks[1].lines(2, 2), vec![vec![0, 3]]);
+
+        assert_eq!(
+            Pattern::parse_masks("###\n#\n##"),
+            Err(PatternError::RaggedMask("mask 1".to_string()))
+        );
+        assert_eq!(
+            Pattern::parse_masks(".."),
+            Err(PatternError::EmptyMask("mask 1".to_string()))
+        );
+        assert_eq!(
+            "spiral".parse::<Pattern>(),
+            Err(PatternError::Unknown("spiral".to_string()))
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+/// Small seeded pseudo random number generator (SplitMix64), so simulations can be
+/// reproduced exactly from their seed.
+#[derive(Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            let j = self.below(i + 1);
+            values.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_random {
+    use super::*;
+
+    #[test]
+    fn test_shuffle() {
+        let mut values: Vec<u32> = (0..20).collect();
+        Random::new(42).shuffle(&mut values);
+
+        let mut other: Vec<u32> = (0..20).collect();
+        Random::new(42).shuffle(&mut other);
+        assert_eq!(values, other);
+        assert_ne!(values, (0..20).collect::<Vec<u32>>());
+
+        values.sort_unstable();
+        assert_eq!(values, (0..20).collect::<Vec<u32>>());
+    }
+}
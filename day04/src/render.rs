@@ -0,0 +1,121 @@
+use crate::bingo::{BingoCard, BingoGame, Point};
+use crate::pattern::Pattern;
+use std::collections::HashSet;
+use std::error::Error;
+use std::time::Duration;
+
+const RESET: &str = "\x1b[0m";
+const CALLED: &str = "\x1b[1;32m";
+const WINNING: &str = "\x1b[1;33;7m";
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    /// Highlights called numbers with colours
+    Ansi,
+    /// Marks called numbers as `[n]` and the winning line as `*n*`
+    Plain,
+}
+
+/// Renders `card` one row per line, highlighting the `called` numbers and emphasising the
+/// `winning` cells.
+pub fn render_card(card: &BingoCard, called: &[Point], winning: &[usize], style: Style) -> String {
+    let called: HashSet<&Point> = called.iter().collect();
+    let size = card
+        .values
+        .iter()
+        .map(|v| v.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    let mut output = String::new();
+    for y in 0..card.height {
+        let mut row = vec![];
+        for x in 0..card.width {
+            let value = match card.get_value(x, y) {
+                Some(value) => value,
+                None => continue,
+            };
+            let text = format!("{:>size$}", value, size = size);
+
+            let cell = if winning.contains(&(x + card.width * y)) {
+                match style {
+                    Style::Ansi => format!(" {}{}{} ", WINNING, text, RESET),
+                    Style::Plain => format!("*{}*", text),
+                }
+            } else if called.contains(value) {
+                match style {
+                    Style::Ansi => format!(" {}{}{} ", CALLED, text, RESET),
+                    Style::Plain => format!("[{}]", text),
+                }
+            } else {
+                format!(" {} ", text)
+            };
+            row.push(cell);
+        }
+        output.push_str(&row.join(""));
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Redraws every card after each call of the game, pausing `delay` in between.
+pub fn step_through(
+    game: &BingoGame,
+    patterns: &[Pattern],
+    style: Style,
+    delay: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let rounds = game.play(patterns)?;
+    let mut winning = vec![vec![]; game.cards.len()];
+
+    for index in 0..game.order.len() {
+        let called = &game.order[..=index];
+        for round in rounds.iter().filter(|r| r.called.len() == called.len()) {
+            for result in &round.results {
+                winning[result.card_index] = result.get_winning_cells();
+            }
+        }
+
+        if style == Style::Ansi {
+            print!("{}", CLEAR);
+        }
+        println!("Call {}: {}", index + 1, game.order[index]);
+        for (card_index, card) in game.cards.iter().enumerate() {
+            println!();
+            println!("Card {}", card_index + 1);
+            print!("{}", render_card(card, called, &winning[card_index], style));
+        }
+        println!();
+
+        std::thread::sleep(delay);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_render {
+    use super::*;
+
+    #[test]
+    fn test_render_card() -> Result<(), Box<dyn Error>> {
+        let card = BingoCard::new(3, 2, vec![1, 2, 13, 4, 5, 6])?;
+
+        assert_eq!(
+            render_card(&card, &[2, 5], &[], Style::Plain),
+            "  1 [ 2] 13 \n  4 [ 5]  6 \n"
+        );
+        assert_eq!(
+            render_card(&card, &[2, 5, 13], &[1, 4], Style::Plain),
+            "  1 * 2*[13]\n  4 * 5*  6 \n"
+        );
+        assert_eq!(
+            render_card(&card, &[13], &[], Style::Ansi),
+            format!("  1   2  {}13{} \n  4   5   6 \n", CALLED, RESET)
+        );
+
+        Ok(())
+    }
+}
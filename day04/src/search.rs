@@ -0,0 +1,252 @@
+use crate::bingo::{BingoCard, BingoGame, Point};
+use crate::pattern::Pattern;
+use std::collections::BTreeSet;
+use std::error::Error;
+
+type Line = BTreeSet<Point>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Goal {
+    /// The card wins before any other card
+    First(usize),
+    /// The card wins after every other card has won
+    Last(usize),
+    /// Every card wins on the same call
+    Together,
+}
+
+impl std::str::FromStr for Goal {
+    type Err = Box<dyn Error>;
+
+    /// Parses `first:<card>`, `last:<card>` or `together`, with cards numbered from 1.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "together" {
+            return Ok(Goal::Together);
+        }
+
+        let (goal, card) = value.split_once(':').ok_or("Missing card number")?;
+        let card = card
+            .parse::<usize>()?
+            .checked_sub(1)
+            .ok_or("Cards start at 1")?;
+        match goal {
+            "first" => Ok(Goal::First(card)),
+            "last" => Ok(Goal::Last(card)),
+            _ => Err(format!("Unknown goal: {}", goal).into()),
+        }
+    }
+}
+
+/// Finds a call order which achieves `goal` for `cards`, or `None` if no order can.
+///
+/// `First` returns the shortest possible order. `Last` and `Together` return an order
+/// which ends on the call that achieves the goal.
+pub fn find_order(cards: &[BingoCard], patterns: &[Pattern], goal: Goal) -> Option<Vec<Point>> {
+    let lines: Vec<Vec<Line>> = cards.iter().map(|c| get_lines(c, patterns)).collect();
+
+    match goal {
+        Goal::First(target) => find_first(&lines, target),
+        Goal::Last(target) => find_last(cards, &lines, target),
+        Goal::Together => find_together(cards, &lines),
+    }
+}
+
+/// Replays `order` through `BingoGame::play` and checks that it achieves `goal`.
+pub fn verify(
+    cards: &[BingoCard],
+    patterns: &[Pattern],
+    goal: Goal,
+    order: &[Point],
+) -> Result<bool, Box<dyn Error>> {
+    let game = BingoGame {
+        order: order.to_vec(),
+        cards: cards.to_vec(),
+    };
+    let rounds = game.play(patterns)?;
+
+    let winners = |round: usize| -> Vec<usize> {
+        rounds[round].results.iter().map(|r| r.card_index).collect()
+    };
+    let won: usize = rounds.iter().map(|r| r.results.len()).sum();
+
+    Ok(match goal {
+        _ if rounds.is_empty() => false,
+        Goal::First(target) => winners(0) == vec![target],
+        Goal::Last(target) => won == cards.len() && winners(rounds.len() - 1) == vec![target],
+        Goal::Together => rounds.len() == 1 && won == cards.len(),
+    })
+}
+
+fn get_lines(card: &BingoCard, patterns: &[Pattern]) -> Vec<Line> {
+    let mut lines: Vec<Line> = patterns
+        .iter()
+        .flat_map(|p| p.lines(card.width, card.height))
+        .map(|cells| cells.iter().map(|c| card.values[*c]).collect())
+        .collect();
+    lines.sort_by_key(|l| l.len());
+    lines
+}
+
+fn completes(lines: &[Line], called: &Line) -> bool {
+    lines.iter().any(|l| l.is_subset(called))
+}
+
+fn find_first(lines: &[Vec<Line>], target: usize) -> Option<Vec<Point>> {
+    // calling exactly one of the target's lines is the shortest way for it to win, as long
+    // as that does not also complete a line on another card
+    lines
+        .get(target)?
+        .iter()
+        .find(|line| {
+            lines
+                .iter()
+                .enumerate()
+                .all(|(card, other)| card == target || !completes(other, line))
+        })
+        .map(|line| line.iter().copied().collect())
+}
+
+fn find_last(cards: &[BingoCard], lines: &[Vec<Line>], target: usize) -> Option<Vec<Point>> {
+    let target_values: Line = cards.get(target)?.values.iter().copied().collect();
+
+    // numbers missing from the target card can be called freely
+    let free: Line = cards
+        .iter()
+        .flat_map(|c| c.values.iter().copied())
+        .filter(|v| !target_values.contains(v))
+        .collect();
+
+    // the remaining cards need some of the target's numbers, which must not add up to a
+    // complete line on the target card
+    let mut remaining: Vec<Vec<Line>> = lines
+        .iter()
+        .enumerate()
+        .filter(|(card, other)| *card != target && !completes(other, &free))
+        .map(|(_, other)| {
+            other
+                .iter()
+                .map(|l| l.intersection(&target_values).copied().collect())
+                .collect()
+        })
+        .collect();
+    remaining.sort_by_key(|l| l.len());
+
+    let needed = search(&remaining, Line::new(), &|called| {
+        !completes(&lines[target], called)
+    })?;
+
+    let finish = lines[target]
+        .iter()
+        .min_by_key(|l| l.difference(&needed).count())?;
+
+    let mut result: Vec<Point> = free.into_iter().collect();
+    result.extend(needed.iter());
+    result.extend(finish.difference(&needed));
+    Some(result)
+}
+
+fn find_together(cards: &[BingoCard], lines: &[Vec<Line>]) -> Option<Vec<Point>> {
+    let mut shared: Line = cards.first()?.values.iter().copied().collect();
+    for card in cards {
+        shared.retain(|v| card.values.contains(v));
+    }
+
+    for last in shared {
+        // every card must complete a line through the last number, using only numbers
+        // which do not complete a line on any card by themselves
+        let choices: Vec<Vec<Line>> = lines
+            .iter()
+            .map(|card| {
+                card.iter()
+                    .filter(|l| l.contains(&last))
+                    .map(|l| l.iter().copied().filter(|v| *v != last).collect())
+                    .collect()
+            })
+            .collect();
+
+        let valid = |called: &Line| lines.iter().all(|card| !completes(card, called));
+        if let Some(called) = search(&choices, Line::new(), &valid) {
+            let mut result: Vec<Point> = called.into_iter().collect();
+            result.push(last);
+            return Some(result);
+        }
+    }
+
+    None
+}
+
+/// Picks one line for each card such that the union of the picked lines stays `valid`.
+fn search(choices: &[Vec<Line>], called: Line, valid: &dyn Fn(&Line) -> bool) -> Option<Line> {
+    let (first, rest) = match choices.split_first() {
+        Some(split) => split,
+        None => return Some(called),
+    };
+
+    for line in first {
+        let next: Line = called.union(line).copied().collect();
+        if valid(&next) {
+            if let Some(result) = search(rest, next, valid) {
+                return Some(result);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test_search {
+    use super::*;
+    use crate::bingo::CardError;
+
+    fn get_cards() -> Result<Vec<BingoCard>, CardError> {
+        Ok(vec![
+            BingoCard::new(2, 2, vec![1, 2, 3, 4])?,
+            BingoCard::new(2, 2, vec![1, 5, 6, 7])?,
+            BingoCard::new(2, 2, vec![8, 1, 9, 2])?,
+        ])
+    }
+
+    #[test]
+    fn test_goals() -> Result<(), Box<dyn Error>> {
+        let cards = get_cards()?;
+        let patterns = Pattern::defaults();
+
+        for goal in [
+            Goal::First(0),
+            Goal::First(1),
+            Goal::First(2),
+            Goal::Last(0),
+            Goal::Last(1),
+            Goal::Last(2),
+            Goal::Together,
+        ] {
+            let order = find_order(&cards, &patterns, goal).ok_or("No order found")?;
+            assert!(verify(&cards, &patterns, goal, &order)?, "{:?}", goal);
+        }
+
+        let order = find_order(&cards, &patterns, Goal::First(1)).ok_or("No order found")?;
+        assert_eq!(order.len(), 2);
+
+        assert_eq!("last:2".parse::<Goal>()?, Goal::Last(1));
+        assert!("first:0".parse::<Goal>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_impossible() -> Result<(), CardError> {
+        let cards = vec![
+            BingoCard::new(2, 2, vec![1, 2, 3, 4])?,
+            BingoCard::new(2, 2, vec![3, 4, 1, 2])?,
+        ];
+        let patterns = Pattern::defaults();
+
+        assert_eq!(find_order(&cards, &patterns, Goal::First(0)), None);
+        assert_eq!(find_order(&cards, &patterns, Goal::Last(1)), None);
+        assert_eq!(find_order(&cards, &patterns, Goal::First(5)), None);
+        assert!(find_order(&cards, &patterns, Goal::Together).is_some());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+use crate::bingo::{BingoGame, BingoResult};
+use crate::pattern::Pattern;
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+/// A bingo game played by clients over a line based TCP protocol.
+///
+/// Clients send:
+///
+/// * `CLAIM <card>` to play a card, answered with `OK <card> <width> <height> <values>`
+/// * `BINGO <card>` once one of their cards has won
+///
+/// The server broadcasts `CALL <number>` for each number called once every client has
+/// claimed a card, then `WINNER <card> <score>` for the first valid claim and `END` when
+/// the game is over. Invalid requests are answered with `ERROR <reason>`.
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    game: BingoGame,
+    patterns: Vec<Pattern>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Winner {
+    pub card: usize,
+    pub score: u32,
+}
+
+#[derive(Debug)]
+enum Message {
+    Connected(usize, TcpStream),
+    Line(usize, String),
+    Disconnected(usize),
+}
+
+#[derive(Debug)]
+struct State {
+    clients: HashMap<usize, TcpStream>,
+    owners: HashMap<usize, usize>,
+    called: usize,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(
+        address: A,
+        game: BingoGame,
+        patterns: Vec<Pattern>,
+    ) -> Result<Server, Box<dyn Error>> {
+        Ok(Server {
+            listener: TcpListener::bind(address)?,
+            game,
+            patterns,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, Box<dyn Error>> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Waits for `players` clients to connect and claim a card, then calls a number every
+    /// `interval` until a client makes a valid claim or the numbers run out.
+    pub fn run(self, players: usize, interval: Duration) -> Result<Option<Winner>, Box<dyn Error>> {
+        let (sender, receiver) = channel();
+
+        let listener = self.listener.try_clone()?;
+        std::thread::spawn(move || accept(listener, sender));
+
+        let mut state = State {
+            clients: HashMap::new(),
+            owners: HashMap::new(),
+            called: 0,
+        };
+
+        while state.clients.len() < players
+            || state
+                .clients
+                .keys()
+                .any(|c| !state.owners.values().any(|o| o == c))
+        {
+            let message = receiver.recv()?;
+            if let Some(winner) = self.handle(&mut state, message) {
+                return Ok(Some(winner));
+            }
+        }
+
+        for number in &self.game.order {
+            state.called += 1;
+            broadcast(&mut state, &format!("CALL {}", number));
+
+            if let Some(winner) = self.wait(&mut state, &receiver, interval)? {
+                broadcast(
+                    &mut state,
+                    &format!("WINNER {} {}", winner.card + 1, winner.score),
+                );
+                broadcast(&mut state, "END");
+                return Ok(Some(winner));
+            }
+        }
+
+        broadcast(&mut state, "END");
+        Ok(None)
+    }
+
+    fn wait(
+        &self,
+        state: &mut State,
+        receiver: &Receiver<Message>,
+        interval: Duration,
+    ) -> Result<Option<Winner>, Box<dyn Error>> {
+        let deadline = Instant::now() + interval;
+
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(timeout) {
+                Ok(message) => {
+                    if let Some(winner) = self.handle(state, message) {
+                        return Ok(Some(winner));
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn handle(&self, state: &mut State, message: Message) -> Option<Winner> {
+        match message {
+            Message::Connected(client, stream) => {
+                state.clients.insert(client, stream);
+                None
+            }
+            Message::Disconnected(client) => {
+                state.clients.remove(&client);
+                state.owners.retain(|_, owner| *owner != client);
+                None
+            }
+            Message::Line(client, line) => {
+                let (response, winner) = match self.respond(state, client, &line) {
+                    Ok((response, winner)) => (response, winner),
+                    Err(e) => (format!("ERROR {}", e), None),
+                };
+                send(state, client, &response);
+                winner
+            }
+        }
+    }
+
+    fn respond(
+        &self,
+        state: &mut State,
+        client: usize,
+        line: &str,
+    ) -> Result<(String, Option<Winner>), Box<dyn Error>> {
+        let mut tokens = line.split_whitespace();
+        let command = tokens.next().ok_or("empty request")?;
+        let index = tokens
+            .next()
+            .ok_or("missing card")?
+            .parse::<usize>()?
+            .checked_sub(1)
+            .ok_or("cards start at 1")?;
+        let card = self.game.cards.get(index).ok_or("unknown card")?;
+
+        match command {
+            "CLAIM" => {
+                if state.owners.contains_key(&index) {
+                    return Err("card already claimed".into());
+                }
+                state.owners.insert(index, client);
+
+                let values: Vec<String> = card.values.iter().map(|v| v.to_string()).collect();
+                let response = format!(
+                    "OK {} {} {} {}",
+                    index + 1,
+                    card.width,
+                    card.height,
+                    values.join(",")
+                );
+                Ok((response, None))
+            }
+            "BINGO" => {
+                if state.owners.get(&index) != Some(&client) {
+                    return Err("not your card".into());
+                }
+
+                // a claim can arrive after further calls, so score the call that won
+                let (called, pattern) = (1..=state.called)
+                    .map(|n| &self.game.order[..n])
+                    .find_map(|called| Some((called, card.check(called, &self.patterns)?)))
+                    .ok_or("not a bingo")?;
+                let result = BingoResult {
+                    called,
+                    card_index: index,
+                    card,
+                    pattern,
+                };
+
+                let winner = Winner {
+                    card: index,
+                    score: result.get_result()?,
+                };
+                Ok((format!("OK {}", index + 1), Some(winner)))
+            }
+            _ => Err(format!("unknown command {}", command).into()),
+        }
+    }
+}
+
+fn accept(listener: TcpListener, sender: Sender<Message>) {
+    for (client, stream) in listener.incoming().enumerate() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let writer = match stream.try_clone() {
+            Ok(writer) => writer,
+            Err(_) => continue,
+        };
+        if sender.send(Message::Connected(client, writer)).is_err() {
+            return;
+        }
+
+        let sender = sender.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if sender.send(Message::Line(client, line)).is_err() {
+                    return;
+                }
+            }
+            let _ = sender.send(Message::Disconnected(client));
+        });
+    }
+}
+
+fn send(state: &mut State, client: usize, line: &str) {
+    if let Some(stream) = state.clients.get_mut(&client) {
+        // clients which went away are removed once their reader notices
+        let _ = writeln!(stream, "{}", line);
+    }
+}
+
+fn broadcast(state: &mut State, line: &str) {
+    for stream in state.clients.values_mut() {
+        let _ = writeln!(stream, "{}", line);
+    }
+}
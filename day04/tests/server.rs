@@ -0,0 +1,147 @@
+use day04::bingo::{first_winner, BingoCard, BingoGame};
+use day04::pattern::Pattern;
+use day04::server::{Server, Winner};
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+const EXAMPLE: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7";
+
+/// Plays `card` honestly: claims it, marks every call and shouts bingo `late` calls after
+/// it wins. Returns every line received from the server.
+fn play(
+    address: SocketAddr,
+    card: usize,
+    late: usize,
+) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    let mut stream = TcpStream::connect(address)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut received = vec![];
+
+    writeln!(stream, "CLAIM {}", card)?;
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    assert_eq!(tokens[0], "OK");
+    let values = tokens[4]
+        .split(',')
+        .map(|v| v.parse())
+        .collect::<Result<_, _>>()?;
+    let card_values = BingoCard::new(tokens[2].parse()?, tokens[3].parse()?, values)?;
+
+    // claiming somebody else's card is rejected
+    writeln!(stream, "BINGO {}", if card == 1 { 2 } else { 1 })?;
+
+    let patterns = Pattern::defaults();
+    let mut called = vec![];
+    let mut won = None;
+    for line in reader.lines() {
+        let line = line?;
+        received.push(line.clone());
+
+        if let Some(number) = line.strip_prefix("CALL ") {
+            called.push(number.parse()?);
+            if won.is_none() && card_values.check(&called, &patterns).is_some() {
+                won = Some(called.len());
+            }
+            if won == Some(called.len() - late) {
+                writeln!(stream, "BINGO {}", card)?;
+            }
+        } else if line == "END" {
+            break;
+        }
+    }
+
+    Ok(received)
+}
+
+#[test]
+fn test_server() -> Result<(), Box<dyn Error>> {
+    let game = BingoGame::parse(EXAMPLE)?;
+    let patterns = Pattern::defaults();
+    let rounds = game.play(&patterns)?;
+    let expected = first_winner(&rounds).ok_or("Missing first result")?;
+    let expected = Winner {
+        card: expected.card_index,
+        score: expected.get_result()?,
+    };
+
+    let server = Server::bind("127.0.0.1:0", BingoGame::parse(EXAMPLE)?, patterns)?;
+    let address = server.local_addr()?;
+    let host = std::thread::spawn(move || {
+        server
+            .run(3, Duration::from_millis(200))
+            .map_err(|e| e.to_string())
+    });
+
+    let clients: Vec<_> = (1..=3)
+        .map(|card| std::thread::spawn(move || play(address, card, 0).map_err(|e| e.to_string())))
+        .collect();
+
+    let winner = host.join().map_err(|_| "server panicked")??;
+    assert_eq!(winner, Some(expected));
+
+    for client in clients {
+        let received = client.join().map_err(|_| "client panicked")??;
+
+        assert!(received.contains(&"ERROR not your card".to_string()));
+        // a slow claim can let another number be called, the winner is scored the same
+        assert!(received.iter().filter(|l| l.starts_with("CALL ")).count() >= 12);
+        assert_eq!(
+            &received[received.len() - 2..],
+            &["WINNER 3 4512".to_string(), "END".to_string()]
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_late_claim() -> Result<(), Box<dyn Error>> {
+    let server = Server::bind(
+        "127.0.0.1:0",
+        BingoGame::parse(EXAMPLE)?,
+        Pattern::defaults(),
+    )?;
+    let address = server.local_addr()?;
+    let host = std::thread::spawn(move || {
+        server
+            .run(1, Duration::from_millis(200))
+            .map_err(|e| e.to_string())
+    });
+
+    // card 3 wins on the 12th call but only claims after the 13th
+    let received = play(address, 3, 1).map_err(|e| e.to_string())?;
+    let winner = host.join().map_err(|_| "server panicked")??;
+
+    assert_eq!(
+        winner,
+        Some(Winner {
+            card: 2,
+            score: 4512
+        })
+    );
+    assert!(received.iter().filter(|l| l.starts_with("CALL ")).count() >= 13);
+
+    Ok(())
+}
@@ -0,0 +1,477 @@
+use crate::vector::{Point, Vector};
+use std::error::Error;
+
+impl std::ops::Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+}
+
+impl std::ops::Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+impl std::ops::Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl std::ops::Mul<i32> for Point {
+    type Output = Self;
+
+    fn mul(self, factor: i32) -> Self {
+        Self {
+            x: self.x * factor,
+            y: self.y * factor,
+            z: self.z * factor,
+        }
+    }
+}
+
+impl Point {
+    /// Distance moving only horizontally and vertically
+    pub fn manhattan(&self, other: &Point) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+
+    /// Distance moving horizontally, vertically or diagonally
+    pub fn chebyshev(&self, other: &Point) -> i32 {
+        (self.x - other.x)
+            .abs()
+            .max((self.y - other.y).abs())
+            .max((self.z - other.z).abs())
+    }
+}
+
+/// The integer points two vectors have in common
+#[derive(Debug, PartialEq)]
+pub enum Intersection {
+    None,
+    Point(Point),
+    /// The vectors are collinear and share more than one point, pointing the same way as
+    /// the vector `intersection` was called on
+    Segment(Vector),
+}
+
+/// Inclusive axis aligned box, one point deep for 2D points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl std::str::FromStr for BoundingBox {
+    type Err = Box<dyn Error>;
+
+    /// Parses the opposite corners `x1,y1,x2,y2`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let values = value
+            .split(',')
+            .map(|v| v.parse())
+            .collect::<Result<Vec<i32>, _>>()?;
+        if values.len() != 4 {
+            return Err("Expected x1,y1,x2,y2".into());
+        }
+
+        Ok(BoundingBox::around([
+            Point {
+                x: values[0],
+                y: values[1],
+                z: 0,
+            },
+            Point {
+                x: values[2],
+                y: values[3],
+                z: 0,
+            },
+        ])
+        .ok_or("Empty box")?)
+    }
+}
+
+impl BoundingBox {
+    /// The smallest box containing every point, `None` if there are none
+    pub fn around<I: IntoIterator<Item = Point>>(points: I) -> Option<BoundingBox> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(
+            BoundingBox {
+                min: first,
+                max: first,
+            },
+            |bounds, p| bounds.extend(p),
+        ))
+    }
+
+    /// Grows the box to also contain `point`
+    pub fn extend(self, point: Point) -> BoundingBox {
+        BoundingBox {
+            min: Point {
+                x: self.min.x.min(point.x),
+                y: self.min.y.min(point.y),
+                z: self.min.z.min(point.z),
+            },
+            max: Point {
+                x: self.max.x.max(point.x),
+                y: self.max.y.max(point.y),
+                z: self.max.z.max(point.z),
+            },
+        }
+    }
+
+    pub fn union(self, other: BoundingBox) -> BoundingBox {
+        self.extend(other.min).extend(other.max)
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.min.x <= point.x
+            && point.x <= self.max.x
+            && self.min.y <= point.y
+            && point.y <= self.max.y
+            && self.min.z <= point.z
+            && point.z <= self.max.z
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.x as i64 - self.min.x as i64 + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y as i64 - self.min.y as i64 + 1) as usize
+    }
+
+    pub fn depth(&self) -> usize {
+        (self.max.z as i64 - self.min.z as i64 + 1) as usize
+    }
+
+    /// Number of points in the box, `i64::MAX` if there are more
+    pub fn area(&self) -> i64 {
+        (self.width() as i64)
+            .saturating_mul(self.height() as i64)
+            .saturating_mul(self.depth() as i64)
+    }
+}
+
+impl Vector {
+    /// Euclidean length from `point_1` to `point_2`
+    pub fn length(&self) -> f64 {
+        let d = self.point_2 - self.point_1;
+        (d.x as f64).hypot(d.y as f64).hypot(d.z as f64)
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            min: self.point_1,
+            max: self.point_1,
+        }
+        .extend(self.point_2)
+    }
+
+    /// Whether `point` lies on the line between `point_1` and `point_2`
+    pub fn contains(&self, point: Point) -> bool {
+        let (a, b) = (self.point_2 - self.point_1, point - self.point_1);
+        cross(a, b) == 0
+            && cross(rotate(a), rotate(b)) == 0
+            && cross(rotate(rotate(a)), rotate(rotate(b))) == 0
+            && self.bounding_box().contains(point)
+    }
+
+    /// Returns the integer points this vector shares with `other`, the same points that
+    /// both of their paths visit.
+    pub fn intersection(&self, other: &Vector) -> Intersection {
+        if !(self.is_flat() && other.is_flat() && self.point_1.z == other.point_1.z) {
+            return self.intersection_3d(other);
+        }
+
+        let d1 = self.point_2 - self.point_1;
+        let d2 = other.point_2 - other.point_1;
+        let zero = Point { x: 0, y: 0, z: 0 };
+
+        // single points either lie on the other vector or not
+        if d1 == zero {
+            return if other.contains(self.point_1) {
+                Intersection::Point(self.point_1)
+            } else {
+                Intersection::None
+            };
+        }
+        if d2 == zero {
+            return other.intersection(self);
+        }
+
+        let denominator = cross(d1, d2);
+        let offset = other.point_1 - self.point_1;
+
+        if denominator == 0 {
+            if cross(d1, offset) != 0 {
+                // parallel but on different lines
+                return Intersection::None;
+            }
+            return self.overlap(other);
+        }
+
+        // solve point_1 + t * d1 = other.point_1 + u * d2 with t and u in [0, 1]
+        let t = cross(offset, d2);
+        let u = cross(offset, d1);
+        let (t, u, denominator) = if denominator < 0 {
+            (-t, -u, -denominator)
+        } else {
+            (t, u, denominator)
+        };
+        if t < 0 || t > denominator || u < 0 || u > denominator {
+            return Intersection::None;
+        }
+
+        let x = self.point_1.x as i64 * denominator + t * d1.x as i64;
+        let y = self.point_1.y as i64 * denominator + t * d1.y as i64;
+        if x % denominator != 0 || y % denominator != 0 {
+            // the lines cross between integer points
+            return Intersection::None;
+        }
+
+        Intersection::Point(Point {
+            x: (x / denominator) as i32,
+            y: (y / denominator) as i32,
+            z: self.point_1.z,
+        })
+    }
+
+    /// Intersection of vectors which do not share a plane of constant z, found by walking
+    /// the path of `self`. Shared points are always contiguous along it, since two lines
+    /// meet in at most one point unless they are the same line.
+    fn intersection_3d(&self, other: &Vector) -> Intersection {
+        let shared: Vec<Point> = self
+            .get_path()
+            .into_iter()
+            .filter(|p| other.contains(*p))
+            .collect();
+
+        match shared[..] {
+            [] => Intersection::None,
+            [point] => Intersection::Point(point),
+            [first, .., last] => Intersection::Segment(Vector {
+                point_1: first,
+                point_2: last,
+            }),
+        }
+    }
+
+    /// Intersection of two vectors on the same line
+    fn overlap(&self, other: &Vector) -> Intersection {
+        // order points along the line by projecting them onto its direction
+        let direction = self.point_2 - self.point_1;
+        let position = |p: Point| p.x as i64 * direction.x as i64 + p.y as i64 * direction.y as i64;
+        let sorted = |v: &Vector| {
+            if position(v.point_1) <= position(v.point_2) {
+                (v.point_1, v.point_2)
+            } else {
+                (v.point_2, v.point_1)
+            }
+        };
+
+        let (start_1, end_1) = sorted(self);
+        let (start_2, end_2) = sorted(other);
+        let start = if position(start_1) >= position(start_2) {
+            start_1
+        } else {
+            start_2
+        };
+        let end = if position(end_1) <= position(end_2) {
+            end_1
+        } else {
+            end_2
+        };
+
+        match position(start).cmp(&position(end)) {
+            std::cmp::Ordering::Greater => Intersection::None,
+            std::cmp::Ordering::Equal => Intersection::Point(start),
+            std::cmp::Ordering::Less => Intersection::Segment(Vector {
+                point_1: start,
+                point_2: end,
+            }),
+        }
+    }
+}
+
+/// z component of the cross product
+fn cross(a: Point, b: Point) -> i64 {
+    a.x as i64 * b.y as i64 - a.y as i64 * b.x as i64
+}
+
+/// Cycles the axes so `cross` yields the other components of the cross product
+fn rotate(point: Point) -> Point {
+    Point {
+        x: point.y,
+        y: point.z,
+        z: point.x,
+    }
+}
+
+#[cfg(test)]
+mod test_geometry {
+    use super::*;
+    use crate::vector::VectorParseError;
+
+    fn point(x: i32, y: i32) -> Point {
+        Point { x, y, z: 0 }
+    }
+
+    fn intersect(a: &str, b: &str) -> Result<Intersection, VectorParseError> {
+        Ok(a.parse::<Vector>()?.intersection(&b.parse()?))
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(point(5, 3) - point(2, 7), point(3, -4));
+        assert_eq!(-point(5, -3), point(-5, 3));
+        assert_eq!(point(5, -3) * 3, point(15, -9));
+        assert_eq!(point(1, 2).manhattan(&point(4, -2)), 7);
+        assert_eq!(point(1, 2).chebyshev(&point(4, -2)), 4);
+    }
+
+    #[test]
+    fn test_vector() -> Result<(), VectorParseError> {
+        let vector: Vector = "0,0 -> 6,3".parse()?;
+
+        assert_eq!(vector.length(), 45f64.sqrt());
+        assert_eq!("1,1 -> 4,5".parse::<Vector>()?.length(), 5.0);
+        assert!(vector.contains(point(4, 2)));
+        assert!(vector.contains(point(6, 3)));
+        assert!(!vector.contains(point(8, 4)));
+        assert!(!vector.contains(point(3, 2)));
+        for p in vector.get_path() {
+            assert!(vector.contains(p));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_intersection() -> Result<(), VectorParseError> {
+        assert_eq!(
+            intersect("0,0 -> 4,4", "0,4 -> 4,0")?,
+            Intersection::Point(point(2, 2))
+        );
+        // crossing at (1.5, 1.5)
+        assert_eq!(intersect("0,0 -> 3,3", "0,3 -> 3,0")?, Intersection::None);
+        assert_eq!(intersect("0,0 -> 2,2", "5,0 -> 5,9")?, Intersection::None);
+        assert_eq!(intersect("0,0 -> 4,0", "0,1 -> 4,1")?, Intersection::None);
+        assert_eq!(
+            intersect("0,0 -> 4,0", "4,0 -> 9,0")?,
+            Intersection::Point(point(4, 0))
+        );
+        assert_eq!(
+            intersect("9,0 -> 2,0", "0,0 -> 4,0")?,
+            Intersection::Segment("4,0 -> 2,0".parse()?)
+        );
+        assert_eq!(
+            intersect("0,0 -> 6,3", "8,4 -> 2,1")?,
+            Intersection::Segment("2,1 -> 6,3".parse()?)
+        );
+        assert_eq!(
+            intersect("3,3 -> 3,3", "0,0 -> 6,6")?,
+            Intersection::Point(point(3, 3))
+        );
+        assert_eq!(
+            intersect("0,0 -> 6,6", "3,3 -> 3,3")?,
+            Intersection::Point(point(3, 3))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_3d() -> Result<(), VectorParseError> {
+        let a = Point { x: 1, y: 2, z: 3 };
+        let b = Point { x: 4, y: 0, z: -1 };
+        assert_eq!(a - b, Point { x: -3, y: 2, z: 4 });
+        assert_eq!(a.manhattan(&b), 9);
+        assert_eq!(a.chebyshev(&b), 4);
+
+        let vector: Vector = "0,0,0 -> 2,4,6".parse()?;
+        assert_eq!(vector.length(), 56f64.sqrt());
+        assert!(vector.contains("1,2,3".parse()?));
+        assert!(!vector.contains("1,2,4".parse()?));
+        assert!(!"0,0 -> 2,4".parse::<Vector>()?.contains("1,2,3".parse()?));
+        assert_eq!(vector.bounding_box().area(), 3 * 5 * 7);
+
+        assert_eq!(
+            intersect("0,0,0 -> 2,4,6", "2,0,0 -> 0,4,6")?,
+            Intersection::Point("1,2,3".parse()?)
+        );
+        assert_eq!(
+            intersect("0,0,0 -> 2,4,6", "1,2,3 -> 3,6,9")?,
+            Intersection::Segment("1,2,3 -> 2,4,6".parse()?)
+        );
+        // same x and y but in different planes
+        assert_eq!(
+            intersect("0,0,1 -> 4,4,1", "0,4,2 -> 4,0,2")?,
+            Intersection::None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bounding_box() -> Result<(), VectorParseError> {
+        let bounds = "5,1 -> 2,7".parse::<Vector>()?.bounding_box();
+
+        assert_eq!(bounds.min, point(2, 1));
+        assert_eq!(bounds.max, point(5, 7));
+        assert_eq!((bounds.width(), bounds.height(), bounds.area()), (4, 7, 28));
+        assert!(bounds.contains(point(2, 7)));
+        assert!(!bounds.contains(point(1, 7)));
+        let everything = BoundingBox {
+            min: Point {
+                x: i32::MIN,
+                y: i32::MIN,
+                z: i32::MIN,
+            },
+            max: Point {
+                x: i32::MAX,
+                y: i32::MAX,
+                z: i32::MAX,
+            },
+        };
+        assert_eq!(everything.area(), i64::MAX);
+        assert_eq!(
+            BoundingBox::around([point(3, 3), point(-1, 4), point(0, 9)]),
+            Some(BoundingBox {
+                min: point(-1, 3),
+                max: point(3, 9)
+            })
+        );
+        assert_eq!(BoundingBox::around(vec![]), None);
+        assert_eq!(
+            bounds.union("0,0 -> 0,0".parse::<Vector>()?.bounding_box()),
+            BoundingBox {
+                min: point(0, 0),
+                max: point(5, 7)
+            }
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use crate::geometry::BoundingBox;
+use crate::vector::{Point, Vector};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Largest bounding box (in cells) the dense backend will allocate
+const MAX_DENSE_CELLS: i64 = 1 << 25;
+
+/// The dense backend is only picked when the bounding box has at most this many cells for
+/// every point on a path, otherwise most of the grid would sit empty.
+const MAX_CELLS_PER_POINT: i64 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    Auto,
+    Dense,
+    Sparse,
+    /// Sparse maps filled by several threads, see `parallel::count_overlaps`
+    Parallel,
+}
+
+impl std::str::FromStr for Backend {
+    type Err = Box<dyn Error>;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(Backend::Auto),
+            "dense" => Ok(Backend::Dense),
+            "sparse" => Ok(Backend::Sparse),
+            "parallel" => Ok(Backend::Parallel),
+            _ => Err(format!("Unknown backend: {}", value).into()),
+        }
+    }
+}
+
+impl Backend {
+    /// Resolves `Auto` to the backend best suited to the bounding box of `vectors`.
+    pub fn choose(self, vectors: &[&Vector]) -> Backend {
+        if self != Backend::Auto {
+            return self;
+        }
+
+        let cells = match get_bounds(vectors) {
+            Some(bounds) => bounds.area(),
+            None => return Backend::Sparse,
+        };
+        let points: i64 = vectors.iter().map(|v| get_length(v)).sum();
+
+        if cells <= MAX_DENSE_CELLS && cells <= points * MAX_CELLS_PER_POINT {
+            Backend::Dense
+        } else {
+            Backend::Sparse
+        }
+    }
+}
+
+pub fn get_bounds(vectors: &[&Vector]) -> Option<BoundingBox> {
+    BoundingBox::around(vectors.iter().flat_map(|v| [v.point_1, v.point_2]))
+}
+
+/// Upper bound of the number of points on the path of `vector`
+fn get_length(vector: &Vector) -> i64 {
+    vector.point_1.chebyshev(&vector.point_2) as i64 + 1
+}
+
+/// Returns how many vectors pass through each point
+pub fn get_counts(vectors: &[&Vector]) -> HashMap<Point, u32> {
+    let mut map: HashMap<Point, u32> = HashMap::new();
+    for vector in vectors {
+        for point in vector.get_path() {
+            let entry = map.entry(point).or_insert(0);
+            *entry += 1;
+        }
+    }
+    map
+}
+
+pub fn count_sparse(vectors: &[&Vector]) -> usize {
+    get_counts(vectors).values().filter(|x| *x > &1).count()
+}
+
+/// Counts overlaps on a grid covering the bounding box of `vectors`, failing when it would
+/// have more than `MAX_DENSE_CELLS` cells.
+pub fn count_dense(vectors: &[&Vector]) -> Result<usize, Box<dyn Error>> {
+    let bounds = match get_bounds(vectors) {
+        Some(bounds) => bounds,
+        None => return Ok(0),
+    };
+    let cells = bounds
+        .width()
+        .checked_mul(bounds.height())
+        .and_then(|c| c.checked_mul(bounds.depth()))
+        .filter(|c| *c as i64 <= MAX_DENSE_CELLS)
+        .ok_or_else(|| {
+            format!(
+                "Bounding box of {} cells is too large for the dense backend",
+                bounds.area()
+            )
+        })?;
+    let min = bounds.min;
+    let (width, height) = (bounds.width(), bounds.height());
+
+    let mut grid = vec![0u16; cells];
+    let mut count = 0;
+    for vector in vectors {
+        for point in vector.get_path() {
+            let index = ((point.z - min.z) as usize * height + (point.y - min.y) as usize) * width
+                + (point.x - min.x) as usize;
+            grid[index] = grid[index].saturating_add(1);
+            if grid[index] == 2 {
+                count += 1;
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod test_grid {
+    use super::*;
+    use crate::vector::{parse_all, EXAMPLE};
+
+    #[test]
+    fn test_backends() -> Result<(), Box<dyn Error>> {
+        let vectors = parse_all(&EXAMPLE[..4])?;
+        let vectors: Vec<&Vector> = vectors.iter().collect();
+
+        assert_eq!(count_dense(&vectors)?, count_sparse(&vectors));
+        assert_eq!(count_dense(&vectors)?, 1);
+        assert_eq!(Backend::Auto.choose(&vectors), Backend::Dense);
+        assert_eq!(Backend::Sparse.choose(&vectors), Backend::Sparse);
+
+        let far: Vector = "0,0 -> 100000,100000".parse()?;
+        let near: Vector = "0,0 -> 0,0".parse()?;
+        assert_eq!(Backend::Auto.choose(&[&far, &near]), Backend::Sparse);
+        assert!(count_dense(&[&far, &near]).is_err());
+
+        let corners = parse_all(&[
+            "-2147483648,-2147483648 -> -2147483648,-2147483648",
+            "2147483647,2147483647 -> 2147483647,2147483647",
+        ])?;
+        let corners: Vec<&Vector> = corners.iter().collect();
+        assert_eq!(Backend::Auto.choose(&corners), Backend::Sparse);
+        assert!(count_dense(&corners).is_err());
+
+        Ok(())
+    }
+}
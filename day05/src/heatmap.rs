@@ -0,0 +1,178 @@
+use crate::geometry::BoundingBox;
+use crate::vector::Point;
+use std::collections::HashMap;
+use std::io::Write;
+
+const RESET: &str = "\x1b[0m";
+
+/// Inclusive rectangle of points to draw
+pub type Region = BoundingBox;
+
+/// The smallest region containing every counted point
+pub fn around(counts: &HashMap<Point, u32>) -> Option<Region> {
+    BoundingBox::around(counts.keys().copied())
+}
+
+/// Adds up the counts of every plane onto z = 0, so 3D inputs are drawn as seen from above
+fn flatten(counts: &HashMap<Point, u32>) -> HashMap<Point, u32> {
+    let mut flat = HashMap::new();
+    for (point, count) in counts {
+        *flat.entry(Point { z: 0, ..*point }).or_insert(0) += count;
+    }
+    flat
+}
+
+/// Every point of the region flattened by `flatten`, one row at a time
+fn rows(region: &Region) -> impl Iterator<Item = impl Iterator<Item = Point>> {
+    let region = *region;
+    (region.min.y..=region.max.y)
+        .map(move |y| (region.min.x..=region.max.x).map(move |x| Point { x, y, z: 0 }))
+}
+
+fn get_max(counts: &HashMap<Point, u32>, region: &Region) -> u32 {
+    rows(region)
+        .flatten()
+        .filter_map(|p| counts.get(&p))
+        .max()
+        .copied()
+        .unwrap_or(0)
+}
+
+/// Scales `count` to 0-255 relative to the hottest point
+fn get_intensity(count: u32, max: u32) -> u8 {
+    if max == 0 {
+        0
+    } else {
+        (count as u64 * 255 / max as u64) as u8
+    }
+}
+
+/// Maps an intensity onto a black-blue-red-yellow-white ramp
+fn get_colour(intensity: u8) -> [u8; 3] {
+    let i = intensity as u32;
+    let [r, g, b] = match i {
+        0..=63 => [0, 0, i * 4],
+        64..=127 => [(i - 64) * 4, 0, 255 - (i - 64) * 4],
+        128..=191 => [255, (i - 128) * 4, 0],
+        _ => [255, 255, (i - 192) * 4],
+    };
+    [r as u8, g as u8, b as u8]
+}
+
+/// Writes a binary greyscale PGM image of the region, brighter points having more overlaps.
+pub fn write_pgm(
+    counts: &HashMap<Point, u32>,
+    region: &Region,
+    writer: &mut dyn Write,
+) -> std::io::Result<()> {
+    let counts = &flatten(counts);
+    let max = get_max(counts, region);
+    writeln!(writer, "P5\n{} {}\n255", region.width(), region.height())?;
+
+    for row in rows(region) {
+        let pixels: Vec<u8> = row
+            .map(|p| get_intensity(*counts.get(&p).unwrap_or(&0), max))
+            .collect();
+        writer.write_all(&pixels)?;
+    }
+    Ok(())
+}
+
+/// Writes a binary colour PPM image of the region.
+pub fn write_ppm(
+    counts: &HashMap<Point, u32>,
+    region: &Region,
+    writer: &mut dyn Write,
+) -> std::io::Result<()> {
+    let counts = &flatten(counts);
+    let max = get_max(counts, region);
+    writeln!(writer, "P6\n{} {}\n255", region.width(), region.height())?;
+
+    for row in rows(region) {
+        let pixels: Vec<u8> = row
+            .flat_map(|p| get_colour(get_intensity(*counts.get(&p).unwrap_or(&0), max)))
+            .collect();
+        writer.write_all(&pixels)?;
+    }
+    Ok(())
+}
+
+/// Renders the region as text, one character per point: `.` for no vectors, the count
+/// itself up to 9 and `#` above that, coloured by intensity when `colour` is set.
+pub fn render(counts: &HashMap<Point, u32>, region: &Region, colour: bool) -> String {
+    let counts = &flatten(counts);
+    let max = get_max(counts, region);
+    let mut output = String::new();
+
+    for row in rows(region) {
+        for point in row {
+            let count = *counts.get(&point).unwrap_or(&0);
+            let character = match count {
+                0 => '.',
+                1..=9 => std::char::from_digit(count, 10).unwrap_or('#'),
+                _ => '#',
+            };
+
+            if colour && count > 0 {
+                let [r, g, b] = get_colour(get_intensity(count, max).max(64));
+                output.push_str(&format!(
+                    "\x1b[38;2;{};{};{}m{}{}",
+                    r, g, b, character, RESET
+                ));
+            } else {
+                output.push(character);
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test_heatmap {
+    use super::*;
+    use std::error::Error;
+
+    fn get_counts() -> HashMap<Point, u32> {
+        [((0, 0), 1), ((1, 0), 2), ((2, 1), 4)]
+            .iter()
+            .map(|((x, y), c)| (Point { x: *x, y: *y, z: 0 }, *c))
+            .collect()
+    }
+
+    #[test]
+    fn test_render() -> Result<(), Box<dyn Error>> {
+        let counts = get_counts();
+        let region = around(&counts).ok_or("Empty map")?;
+
+        assert_eq!(region, "2,1,0,0".parse()?);
+        assert_eq!(render(&counts, &region, false), "12.\n..4\n");
+        assert_eq!(render(&counts, &"1,0,3,0".parse()?, false), "2..\n");
+
+        let mut planes = counts.clone();
+        planes.insert(Point { x: 0, y: 0, z: 3 }, 2);
+        planes.insert(Point { x: 1, y: 1, z: -1 }, 1);
+        let region = around(&planes).ok_or("Empty map")?;
+        assert_eq!(render(&planes, &region, false), "32.\n.14\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_pgm() -> Result<(), Box<dyn Error>> {
+        let counts = get_counts();
+        let region = around(&counts).ok_or("Empty map")?;
+
+        let mut buffer = vec![];
+        write_pgm(&counts, &region, &mut buffer)?;
+        assert_eq!(&buffer[..11], b"P5\n3 2\n255\n");
+        assert_eq!(&buffer[11..], &[63, 127, 0, 0, 0, 255]);
+
+        let mut buffer = vec![];
+        write_ppm(&counts, &region, &mut buffer)?;
+        assert_eq!(buffer.len(), 11 + 3 * 6);
+
+        Ok(())
+    }
+}
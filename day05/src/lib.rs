@@ -0,0 +1,7 @@
+pub mod geometry;
+pub mod grid;
+pub mod heatmap;
+pub mod parallel;
+pub mod query;
+pub mod sweep;
+pub mod vector;
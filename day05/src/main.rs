@@ -1,9 +1,15 @@
-mod vector;
-
-use std::collections::HashMap;
+use day05::grid::{self, Backend};
+use day05::heatmap;
+use day05::parallel;
+use day05::query::{self, Overlaps};
+use day05::sweep;
+use day05::vector::{Vector, VectorType};
 use std::error::Error;
+use std::fs::File;
 use std::io::Read;
-use vector::{Point, Vector, VectorType};
+use std::time::Instant;
+
+type Counter = dyn Fn(&[&Vector]) -> Result<usize, Box<dyn Error>>;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let mut buffer = String::new();
@@ -15,39 +21,186 @@ fn main() -> Result<(), Box<dyn Error>> {
         vectors.push(line.parse::<Vector>()?);
     }
 
-    let part_1 = calculate_part_1(&vectors);
+    if has_flag("--benchmark") {
+        benchmark(&vectors, get_threads()?);
+        return Ok(());
+    }
+
+    if query(&vectors)? {
+        return Ok(());
+    }
+
+    let backend = get_arg("--backend=").map_or(Ok(Backend::Auto), |b| b.parse())?;
+    let threads = get_threads()?;
+    let sweep = has_flag("--sweep");
+    let count = move |vectors: &[&Vector]| {
+        if sweep {
+            Ok(sweep::count_overlaps(vectors))
+        } else {
+            calculate(vectors, backend, threads)
+        }
+    };
+
+    let part_1 = calculate_part_1(&vectors, &count)?;
     println!("Part 1: {}", part_1);
 
-    let part_2 = calculate_part_2(&vectors);
+    let part_2 = calculate_part_2(&vectors, &count)?;
     println!("Part 2: {}", part_2);
 
+    export_heatmap(&vectors)?;
+
     Ok(())
 }
 
-fn calculate_part_1(vectors: &[Vector]) -> usize {
-    // only straight lined vectors
-    let vectors: Vec<&Vector> = vectors
-        .iter()
-        .filter(|v| v.get_type() != VectorType::Diagonal)
-        .collect();
+/// Draws the overlaps of all vectors, added up over every plane, as requested by
+/// `--heatmap`, `--pgm=<path>` and `--ppm=<path>`, limited to `--crop=x1,y1,x2,y2` when
+/// given.
+fn export_heatmap(vectors: &[Vector]) -> Result<(), Box<dyn Error>> {
+    let pgm = get_arg("--pgm=");
+    let ppm = get_arg("--ppm=");
+    if !has_flag("--heatmap") && pgm.is_none() && ppm.is_none() {
+        return Ok(());
+    }
+
+    let vectors: Vec<&Vector> = vectors.iter().collect();
+    let counts = grid::get_counts(&vectors);
+    let region = match get_arg("--crop=") {
+        Some(crop) => crop.parse()?,
+        None => heatmap::around(&counts).ok_or("Nothing to draw")?,
+    };
+
+    if has_flag("--heatmap") {
+        print!(
+            "{}",
+            heatmap::render(&counts, &region, !has_flag("--plain"))
+        );
+    }
+    if let Some(path) = pgm {
+        heatmap::write_pgm(&counts, &region, &mut File::create(path)?)?;
+    }
+    if let Some(path) = ppm {
+        heatmap::write_ppm(&counts, &region, &mut File::create(path)?)?;
+    }
+
+    Ok(())
+}
+
+/// Answers `--histogram`, `--at-least=<k>` and `--hottest=<n>` about the vectors of the
+/// types in `--types=<type,...>` (all of them by default), returning whether any was asked.
+fn query(vectors: &[Vector]) -> Result<bool, Box<dyn Error>> {
+    let at_least = get_arg("--at-least=");
+    let hottest = get_arg("--hottest=");
+    if !has_flag("--histogram") && at_least.is_none() && hottest.is_none() {
+        return Ok(false);
+    }
+
+    let vectors = match get_arg("--types=") {
+        Some(types) => query::filter(vectors, &query::parse_types(&types)?),
+        None => vectors.iter().collect(),
+    };
+    let overlaps = Overlaps::new(&vectors);
+
+    if has_flag("--histogram") {
+        for (count, points) in overlaps.histogram() {
+            println!("{:>3} vectors: {} points", count, points);
+        }
+    }
+    if let Some(k) = at_least {
+        let k = k.parse()?;
+        println!(
+            "Points with at least {} vectors: {}",
+            k,
+            overlaps.at_least(k)
+        );
+    }
+    if let Some(n) = hottest {
+        for hotspot in overlaps.hottest(n.parse()?) {
+            println!("{}: {} vectors", hotspot.point, hotspot.count);
+            for vector in hotspot.vectors {
+                println!("    {}", vector);
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+fn has_flag(flag: &str) -> bool {
+    std::env::args().any(|a| a == flag)
+}
+
+fn get_arg(prefix: &str) -> Option<String> {
+    std::env::args().find_map(|a| a.strip_prefix(prefix).map(String::from))
+}
+
+fn calculate_part_1(vectors: &[Vector], count: &Counter) -> Result<usize, Box<dyn Error>> {
+    // only straight lined vectors, along any of the axes
+    let vectors = query::filter(
+        vectors,
+        &[
+            VectorType::Horizontal,
+            VectorType::Vertical,
+            VectorType::Depth,
+        ],
+    );
 
-    calculate(&vectors)
+    count(&vectors)
 }
 
-fn calculate_part_2(vectors: &[Vector]) -> usize {
+fn calculate_part_2(vectors: &[Vector], count: &Counter) -> Result<usize, Box<dyn Error>> {
     let vectors: Vec<&Vector> = vectors.iter().collect();
-    calculate(&vectors)
+    count(&vectors)
 }
 
-fn calculate(vectors: &[&Vector]) -> usize {
-    let mut map: HashMap<Point, u32> = HashMap::new();
-    for vector in vectors {
-        for point in vector.get_path() {
-            let entry = map.entry(point).or_insert(0);
-            *entry += 1;
-        }
+fn calculate(
+    vectors: &[&Vector],
+    backend: Backend,
+    threads: usize,
+) -> Result<usize, Box<dyn Error>> {
+    match backend.choose(vectors) {
+        Backend::Dense => grid::count_dense(vectors),
+        Backend::Parallel => Ok(parallel::count_overlaps(vectors, threads)),
+        _ => Ok(grid::count_sparse(vectors)),
+    }
+}
+
+/// Worker threads for the parallel backend from `--threads=<n>`, one per core by default
+fn get_threads() -> Result<usize, Box<dyn Error>> {
+    match get_arg("--threads=") {
+        Some(threads) => Ok(threads.parse()?),
+        None => Ok(std::thread::available_parallelism().map_or(1, |n| n.get())),
     }
+}
+
+/// Times every way of counting overlaps on all of `vectors`.
+fn benchmark(vectors: &[Vector], threads: usize) {
+    const RUNS: u32 = 10;
 
-    let count = map.values().filter(|x| *x > &1).count();
-    count
+    let vectors: Vec<&Vector> = vectors.iter().collect();
+    println!("Auto backend: {:?}", Backend::Auto.choose(&vectors));
+
+    let sparse = |vectors: &[&Vector]| Ok(grid::count_sparse(vectors));
+    let parallel = move |vectors: &[&Vector]| Ok(parallel::count_overlaps(vectors, threads));
+    let sweep = |vectors: &[&Vector]| Ok(sweep::count_overlaps(vectors));
+    let counters: [(&str, &Counter); 4] = [
+        ("dense", &grid::count_dense),
+        ("sparse", &sparse),
+        ("parallel", &parallel),
+        ("sweep", &sweep),
+    ];
+    for (name, count) in counters {
+        let start = Instant::now();
+        let mut result = Ok(0);
+        for _ in 0..RUNS {
+            result = count(&vectors);
+            if result.is_err() {
+                break;
+            }
+        }
+        let elapsed = start.elapsed() / RUNS;
+        match result {
+            Ok(result) => println!("{:>8}: {:>10.3?} per run ({})", name, elapsed, result),
+            Err(e) => println!("{:>8}: skipped ({})", name, e),
+        }
+    }
 }
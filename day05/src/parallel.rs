@@ -0,0 +1,115 @@
+use crate::vector::{Point, Vector};
+use std::collections::HashMap;
+
+/// Returns how many vectors pass through each point, rasterising on `threads` workers.
+///
+/// Each worker walks an equal share of the vectors, sorting the points into one map per
+/// shard. Every shard is then merged by its own worker, so no two threads ever write to
+/// the same map and the result does not depend on scheduling.
+pub fn get_counts(vectors: &[&Vector], threads: usize) -> Vec<HashMap<Point, u32>> {
+    let threads = threads.max(1);
+    let chunk = vectors.len().div_ceil(threads).max(1);
+
+    let partials: Vec<Vec<HashMap<Point, u32>>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = vectors
+            .chunks(chunk)
+            .map(|vectors| scope.spawn(move || rasterise(vectors, threads)))
+            .collect();
+        workers
+            .into_iter()
+            .map(|w| w.join().expect("rasterising worker panicked"))
+            .collect()
+    });
+
+    // transpose so each shard holds the partial maps of every worker
+    let mut shards: Vec<Vec<HashMap<Point, u32>>> = (0..threads).map(|_| vec![]).collect();
+    for partial in partials {
+        for (shard, map) in partial.into_iter().enumerate() {
+            shards[shard].push(map);
+        }
+    }
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = shards
+            .into_iter()
+            .map(|maps| scope.spawn(move || merge(maps)))
+            .collect();
+        workers
+            .into_iter()
+            .map(|w| w.join().expect("merging worker panicked"))
+            .collect()
+    })
+}
+
+pub fn count_overlaps(vectors: &[&Vector], threads: usize) -> usize {
+    get_counts(vectors, threads)
+        .iter()
+        .map(|shard| shard.values().filter(|x| **x > 1).count())
+        .sum()
+}
+
+fn rasterise(vectors: &[&Vector], shards: usize) -> Vec<HashMap<Point, u32>> {
+    let mut maps: Vec<HashMap<Point, u32>> = (0..shards).map(|_| HashMap::new()).collect();
+    for vector in vectors {
+        for point in vector.get_path() {
+            *maps[get_shard(&point, shards)].entry(point).or_insert(0) += 1;
+        }
+    }
+    maps
+}
+
+fn merge(maps: Vec<HashMap<Point, u32>>) -> HashMap<Point, u32> {
+    let mut maps = maps.into_iter();
+    let mut merged = maps.next().unwrap_or_default();
+    for map in maps {
+        for (point, count) in map {
+            *merged.entry(point).or_insert(0) += count;
+        }
+    }
+    merged
+}
+
+/// Fixed mix of the coordinates, so a point lands in the same shard on every run
+fn get_shard(point: &Point, shards: usize) -> usize {
+    let hash = (point.x as u32 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (point.y as u32 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ (point.z as u32 as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+    ((hash >> 32) % shards as u64) as usize
+}
+
+#[cfg(test)]
+mod test_parallel {
+    use super::*;
+    use crate::grid;
+
+    #[test]
+    fn test_against_map() {
+        let vectors: Vec<Vector> = (0..200)
+            .map(|i| Vector {
+                point_1: Point {
+                    x: i % 17,
+                    y: i % 11,
+                    z: 0,
+                },
+                point_2: Point {
+                    x: (i * 7) % 23,
+                    y: (i * 5) % 19,
+                    z: 0,
+                },
+            })
+            .collect();
+        let vectors: Vec<&Vector> = vectors.iter().collect();
+        let expected = grid::get_counts(&vectors);
+
+        for threads in [0, 1, 3, 8, 500] {
+            let shards = get_counts(&vectors, threads);
+            assert_eq!(shards.len(), threads.max(1));
+            let merged: HashMap<Point, u32> = shards.into_iter().flatten().collect();
+            assert_eq!(merged, expected, "{} threads", threads);
+            assert_eq!(
+                count_overlaps(&vectors, threads),
+                grid::count_sparse(&vectors)
+            );
+        }
+    }
+}
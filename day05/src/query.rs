@@ -0,0 +1,133 @@
+use crate::grid;
+use crate::vector::{Point, Vector, VectorType};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+/// How many vectors pass through every point, kept alongside the vectors so questions about
+/// the overlaps can be answered without walking the paths again.
+#[derive(Debug)]
+pub struct Overlaps<'a> {
+    vectors: Vec<&'a Vector>,
+    counts: HashMap<Point, u32>,
+}
+
+/// A point together with every vector passing through it
+#[derive(Debug, PartialEq)]
+pub struct Hotspot<'a> {
+    pub point: Point,
+    pub count: u32,
+    pub vectors: Vec<&'a Vector>,
+}
+
+/// Parses a comma separated list of vector types, e.g. `horizontal,vertical`
+pub fn parse_types(value: &str) -> Result<Vec<VectorType>, Box<dyn Error>> {
+    Ok(value
+        .split(',')
+        .map(|t| t.parse())
+        .collect::<Result<_, _>>()?)
+}
+
+/// Keeps the vectors of any of the given types
+pub fn filter<'a>(vectors: &'a [Vector], types: &[VectorType]) -> Vec<&'a Vector> {
+    vectors
+        .iter()
+        .filter(|v| types.contains(&v.get_type()))
+        .collect()
+}
+
+impl<'a> Overlaps<'a> {
+    pub fn new(vectors: &[&'a Vector]) -> Overlaps<'a> {
+        Overlaps {
+            vectors: vectors.to_vec(),
+            counts: grid::get_counts(vectors),
+        }
+    }
+
+    /// Number of points for each number of overlapping vectors
+    pub fn histogram(&self) -> BTreeMap<u32, usize> {
+        let mut histogram = BTreeMap::new();
+        for count in self.counts.values() {
+            *histogram.entry(*count).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Number of points covered by at least `k` vectors
+    pub fn at_least(&self, k: u32) -> usize {
+        self.counts.values().filter(|c| **c >= k).count()
+    }
+
+    /// The `n` points covered by the most vectors, ties ordered by plane, top to bottom
+    /// then left to right.
+    pub fn hottest(&self, n: usize) -> Vec<Hotspot<'a>> {
+        let mut points: Vec<(&Point, &u32)> = self.counts.iter().collect();
+        points.sort_unstable_by_key(|(p, c)| (std::cmp::Reverse(**c), p.z, p.y, p.x));
+
+        points
+            .into_iter()
+            .take(n)
+            .map(|(point, count)| Hotspot {
+                point: *point,
+                count: *count,
+                vectors: self
+                    .vectors
+                    .iter()
+                    .filter(|v| v.contains(*point))
+                    .copied()
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_query {
+    use super::*;
+    use crate::vector::{parse_all, EXAMPLE};
+
+    #[test]
+    fn test_overlaps() -> Result<(), Box<dyn Error>> {
+        let vectors = parse_all(&EXAMPLE)?;
+        let all: Vec<&Vector> = vectors.iter().collect();
+        let overlaps = Overlaps::new(&all);
+
+        assert_eq!(overlaps.at_least(2), 12);
+        assert_eq!(overlaps.at_least(3), 2);
+        assert_eq!(overlaps.at_least(1), overlaps.counts.len());
+        assert_eq!(
+            overlaps.histogram().into_iter().collect::<Vec<_>>(),
+            vec![(1, 27), (2, 10), (3, 2)]
+        );
+
+        let hottest = overlaps.hottest(3);
+        assert_eq!(hottest[0].point, Point { x: 4, y: 4, z: 0 });
+        assert_eq!(hottest[0].count, 3);
+        assert_eq!(
+            hottest[0].vectors,
+            vec![&vectors[1], &vectors[2], &vectors[8]]
+        );
+        assert_eq!(hottest[1].point, Point { x: 6, y: 4, z: 0 });
+        assert_eq!(hottest[2].count, 2);
+        assert_eq!(hottest[2].vectors.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter() -> Result<(), Box<dyn Error>> {
+        let vectors = parse_all(&EXAMPLE)?;
+
+        let straight = filter(&vectors, &parse_types("horizontal,vertical")?);
+        assert_eq!(straight.len(), 6);
+        assert_eq!(Overlaps::new(&straight).at_least(2), 5);
+
+        assert_eq!(filter(&vectors, &parse_types("diagonal")?).len(), 4);
+        assert!(parse_types("curved").is_err());
+
+        let vectors = parse_all(&["1,1,0 -> 1,1,5", "0,0,0 -> 4,0,0", "1,1,2 -> 1,1,2"])?;
+        let depth = filter(&vectors, &parse_types("depth")?);
+        assert_eq!(depth, vec![&vectors[0]]);
+
+        Ok(())
+    }
+}
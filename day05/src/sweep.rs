@@ -0,0 +1,406 @@
+use crate::geometry::Intersection;
+use crate::grid;
+use crate::vector::{gcd, Point, Vector};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// An infinite line through integer points, identified by its primitive direction and its
+/// offset from the origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Line {
+    dx: i64,
+    dy: i64,
+    offset: i64,
+}
+
+#[derive(Debug)]
+struct Segment<'a> {
+    vector: &'a Vector,
+    line: Line,
+    start: i64,
+    end: i64,
+}
+
+/// Counts the points covered by at least two vectors without visiting every point.
+///
+/// Vectors on the same line are merged as intervals along that line, which covers every
+/// collinear overlap. Vectors on different lines can only meet at a single point. Where
+/// horizontal and vertical vectors cross is found by sweeping a line across the plane, so
+/// only vectors at other angles are intersected with every other vector.
+///
+/// Vectors lying in different planes of constant z never meet, so each plane is counted
+/// on its own. Only the paths of vectors crossing between planes are walked, checking each
+/// of their points against the lines of its plane.
+pub fn count_overlaps(vectors: &[&Vector]) -> usize {
+    let (flat, tilted): (Vec<&Vector>, Vec<&Vector>) =
+        vectors.iter().copied().partition(|v| v.is_flat());
+
+    let mut planes: HashMap<i32, Vec<Segment>> = HashMap::new();
+    for vector in flat {
+        planes
+            .entry(vector.point_1.z)
+            .or_default()
+            .push(Segment::new(vector));
+    }
+    let planes: HashMap<i32, LineIndex> = planes
+        .iter()
+        .map(|(z, segments)| (*z, LineIndex::new(segments)))
+        .collect();
+
+    let mut count: usize = planes.values().map(count_plane).sum();
+
+    // a point of a tilted path is new when it is covered twice counting the flat vectors
+    // through it, unless those alone already covered it twice
+    for (point, tilted) in grid::get_counts(&tilted) {
+        let flat = planes
+            .get(&point.z)
+            .map_or(0, |plane| plane.count_through(point));
+        if flat < 2 && flat + tilted as usize >= 2 {
+            count += 1;
+        }
+    }
+
+    count
+}
+
+/// The segments of one plane grouped by the line they lie on
+#[derive(Debug)]
+struct LineIndex<'a> {
+    lines: HashMap<Line, Vec<&'a Segment<'a>>>,
+    directions: HashSet<(i64, i64)>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(segments: &'a [Segment<'a>]) -> LineIndex<'a> {
+        let mut lines: HashMap<Line, Vec<&Segment>> = HashMap::new();
+        for segment in segments {
+            lines.entry(segment.line).or_default().push(segment);
+        }
+        let directions = lines.keys().map(|l| (l.dx, l.dy)).collect();
+        LineIndex { lines, directions }
+    }
+
+    /// Number of segments through `point`, which only needs looking at the line through it
+    /// in each direction
+    fn count_through(&self, point: Point) -> usize {
+        self.directions
+            .iter()
+            .filter_map(|(dx, dy)| self.lines.get(&Line::through(*dx, *dy, point)))
+            .flatten()
+            .filter(|segment| segment.contains(point))
+            .count()
+    }
+}
+
+fn count_plane(index: &LineIndex) -> usize {
+    let lines = &index.lines;
+    let segments: Vec<&Segment> = lines.values().flatten().copied().collect();
+
+    let overlaps: HashMap<Line, Vec<(i64, i64)>> = lines
+        .iter()
+        .map(|(line, segments)| (*line, get_overlaps(segments)))
+        .collect();
+
+    let mut count: i64 = 0;
+    for (line, regions) in &overlaps {
+        let spacing = line.dx * line.dx + line.dy * line.dy;
+        count += regions
+            .iter()
+            .map(|(start, end)| (end - start) / spacing + 1)
+            .sum::<i64>();
+    }
+
+    let (straight, sloped): (Vec<&Segment>, Vec<&Segment>) = segments
+        .into_iter()
+        .partition(|s| s.is_horizontal() || s.is_vertical());
+
+    let mut crossings: HashMap<Point, HashSet<Line>> = HashMap::new();
+    let mut add = |point: Point, a: Line, b: Line| {
+        let lines = crossings.entry(point).or_default();
+        lines.insert(a);
+        lines.insert(b);
+    };
+
+    for (point, a, b) in get_straight_crossings(&straight) {
+        add(point, a, b);
+    }
+    for (index, a) in sloped.iter().enumerate() {
+        for b in straight.iter().chain(&sloped[index + 1..]) {
+            // overlaps on the same line are handled by merging the lines
+            if a.line == b.line {
+                continue;
+            }
+            if let Intersection::Point(point) = a.vector.intersection(b.vector) {
+                add(point, a.line, b.line);
+            }
+        }
+    }
+
+    // a crossing is only new if it is not already part of a collinear overlap, and it is
+    // counted more than once if it lies in the overlaps of several lines
+    for (point, lines) in &crossings {
+        let covered = lines
+            .iter()
+            .filter(|line| {
+                let position = line.position(*point);
+                overlaps[line]
+                    .iter()
+                    .any(|(start, end)| *start <= position && position <= *end)
+            })
+            .count() as i64;
+        count += 1 - covered;
+    }
+
+    count as usize
+}
+
+/// Returns every point where a horizontal segment crosses a vertical one, along with their
+/// lines.
+///
+/// A sweep line moves from left to right, keeping the rows of the horizontal segments it
+/// passes through. Each vertical segment then only looks up the rows within its range.
+fn get_straight_crossings(segments: &[&Segment]) -> Vec<(Point, Line, Line)> {
+    // horizontal segments start before and end after the vertical ones at the same x,
+    // since the segments are inclusive
+    let mut events: Vec<(i32, i32, &Segment)> = vec![];
+    for segment in segments {
+        let (point_1, point_2) = (segment.vector.point_1, segment.vector.point_2);
+        if segment.is_horizontal() {
+            events.push((point_1.x.min(point_2.x), 0, segment));
+            events.push((point_1.x.max(point_2.x), 2, segment));
+        } else {
+            events.push((point_1.x, 1, segment));
+        }
+    }
+    events.sort_unstable_by_key(|(x, kind, _)| (*x, *kind));
+
+    let mut rows: BTreeMap<i32, (usize, Line)> = BTreeMap::new();
+    let mut crossings = vec![];
+    for (x, kind, segment) in events {
+        let (point_1, point_2) = (segment.vector.point_1, segment.vector.point_2);
+        match kind {
+            0 => rows.entry(point_1.y).or_insert((0, segment.line)).0 += 1,
+            1 => {
+                let range = point_1.y.min(point_2.y)..=point_1.y.max(point_2.y);
+                for (y, (_, line)) in rows.range(range) {
+                    let point = Point {
+                        x,
+                        y: *y,
+                        z: point_1.z,
+                    };
+                    crossings.push((point, *line, segment.line));
+                }
+            }
+            _ => {
+                if let Some(row) = rows.get_mut(&point_1.y) {
+                    row.0 -= 1;
+                    if row.0 == 0 {
+                        rows.remove(&point_1.y);
+                    }
+                }
+            }
+        }
+    }
+
+    crossings
+}
+
+/// Returns the ranges along a line covered by at least two of its segments.
+fn get_overlaps(segments: &[&Segment]) -> Vec<(i64, i64)> {
+    // starts sort before ends at the same position since the segments are inclusive
+    let mut events: Vec<(i64, i32)> = segments
+        .iter()
+        .flat_map(|s| [(s.start, 0), (s.end, 1)])
+        .collect();
+    events.sort_unstable();
+
+    let mut regions = vec![];
+    let mut depth = 0;
+    let mut region_start = 0;
+    for (position, kind) in events {
+        if kind == 0 {
+            depth += 1;
+            if depth == 2 {
+                region_start = position;
+            }
+        } else {
+            if depth == 2 {
+                regions.push((region_start, position));
+            }
+            depth -= 1;
+        }
+    }
+
+    regions
+}
+
+impl Line {
+    /// The line in direction `(dx, dy)` passing through `point`
+    fn through(dx: i64, dy: i64, point: Point) -> Line {
+        Line {
+            dx,
+            dy,
+            offset: dy * point.x as i64 - dx * point.y as i64,
+        }
+    }
+
+    /// Also true for single points, which are treated as horizontal
+    fn is_horizontal(&self) -> bool {
+        (self.dx, self.dy) == (1, 0)
+    }
+
+    fn is_vertical(&self) -> bool {
+        (self.dx, self.dy) == (0, 1)
+    }
+
+    fn position(&self, point: Point) -> i64 {
+        self.dx * point.x as i64 + self.dy * point.y as i64
+    }
+}
+
+impl<'a> Segment<'a> {
+    /// Whether `point` on the line of the segment lies within it
+    fn contains(&self, point: Point) -> bool {
+        let position = self.line.position(point);
+        self.start <= position && position <= self.end
+    }
+
+    fn is_horizontal(&self) -> bool {
+        self.line.is_horizontal()
+    }
+
+    fn is_vertical(&self) -> bool {
+        self.line.is_vertical()
+    }
+
+    fn new(vector: &'a Vector) -> Segment<'a> {
+        let difference = vector.point_2 - vector.point_1;
+        let (dx, dy) = (difference.x as i64, difference.y as i64);
+        let steps = gcd(difference.x.abs(), difference.y.abs()) as i64;
+
+        // single points are treated as horizontal so they merge with horizontal vectors
+        let step = if steps == 0 {
+            (1, 0)
+        } else {
+            (dx / steps, dy / steps)
+        };
+        let direction = if step.0 < 0 || (step.0 == 0 && step.1 < 0) {
+            (-step.0, -step.1)
+        } else {
+            step
+        };
+
+        let line = Line::through(direction.0, direction.1, vector.point_1);
+        let (start, end) = (line.position(vector.point_1), line.position(vector.point_2));
+
+        Segment {
+            vector,
+            line,
+            start: start.min(end),
+            end: start.max(end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_sweep {
+    use super::*;
+    use crate::grid::count_sparse;
+    use crate::vector::{parse_all, VectorParseError, EXAMPLE};
+
+    #[test]
+    fn test_example() -> Result<(), VectorParseError> {
+        let vectors = parse_all(&EXAMPLE)?;
+        let vectors: Vec<&Vector> = vectors.iter().collect();
+
+        assert_eq!(count_overlaps(&vectors), 12);
+        assert_eq!(count_overlaps(&vectors), count_sparse(&vectors));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_3d() -> Result<(), VectorParseError> {
+        let planes = parse_all(&[
+            "0,0,0 -> 4,4,0",
+            "0,4,0 -> 4,0,0",
+            "0,0,1 -> 4,4,1",
+            "4,4,1 -> 2,2,1",
+        ])?;
+        let planes: Vec<&Vector> = planes.iter().collect();
+        assert_eq!(count_overlaps(&planes), 4);
+        assert_eq!(count_overlaps(&planes), count_sparse(&planes));
+
+        let crossing = parse_all(&["0,0,0 -> 4,4,4", "2,2,0 -> 2,2,4", "0,0,0 -> 4,4,0"])?;
+        let crossing: Vec<&Vector> = crossing.iter().collect();
+        assert_eq!(count_overlaps(&crossing), 3);
+        assert_eq!(count_overlaps(&crossing), count_sparse(&crossing));
+
+        // tilted vectors meeting each other, single flat vectors and points the flat
+        // vectors already cover twice
+        let mixed = parse_all(&[
+            "0,0,0 -> 6,6,6",
+            "6,0,0 -> 0,6,6",
+            "0,3,3 -> 6,3,3",
+            "3,0,3 -> 3,6,3",
+            "0,2,2 -> 4,2,2",
+            "3,3,0 -> 3,3,6",
+            "5,5,5 -> 5,5,5",
+        ])?;
+        let mixed: Vec<&Vector> = mixed.iter().collect();
+        assert_eq!(count_overlaps(&mixed), count_sparse(&mixed));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_against_map() {
+        // deterministic pseudo random vectors of all slopes, including single points
+        let mut state: u64 = 12345;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) % 12) as i32
+        };
+
+        let mut vectors = vec![];
+        for _ in 0..300 {
+            let point_1 = Point {
+                x: next(),
+                y: next(),
+                z: 0,
+            };
+            let point_2 = match next() % 4 {
+                0 => Point {
+                    x: next(),
+                    y: point_1.y,
+                    z: 0,
+                },
+                1 => Point {
+                    x: point_1.x,
+                    y: next(),
+                    z: 0,
+                },
+                2 => {
+                    let d = next() - 6;
+                    Point {
+                        x: point_1.x + d,
+                        y: point_1.y - d,
+                        z: 0,
+                    }
+                }
+                _ => Point {
+                    x: next(),
+                    y: next(),
+                    z: 0,
+                },
+            };
+            vectors.push(Vector { point_1, point_2 });
+        }
+
+        for size in [1, 2, 5, 20, 300] {
+            let vectors: Vec<&Vector> = vectors.iter().take(size).collect();
+            assert_eq!(count_overlaps(&vectors), count_sparse(&vectors), "{}", size);
+        }
+    }
+}
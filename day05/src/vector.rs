@@ -4,9 +4,11 @@ use thiserror::Error;
 pub struct Point {
     pub x: i32,
     pub y: i32,
+    /// 0 for points parsed without a third coordinate
+    pub z: i32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Vector {
     pub point_1: Point,
     pub point_2: Point,
@@ -16,33 +18,36 @@ pub struct Vector {
 pub enum VectorType {
     Horizontal,
     Vertical,
+    /// Only z changes
+    Depth,
+    /// 45 degree angle, every changing coordinate changes by the same amount
     Diagonal,
+    /// Any other angle
+    Sloped,
 }
 
 impl Vector {
+    /// Returns every integer point on the line from `point_1` to `point_2`, stepping by
+    /// the difference between them divided by its gcd.
     pub fn get_path(&self) -> Vec<Point> {
-        let mut points = vec![];
-        let x = if self.point_1.x <= self.point_2.x {
-            1
-        } else {
-            -1
-        };
-        let y = if self.point_1.y <= self.point_2.y {
-            1
-        } else {
-            -1
-        };
-        let increase = match self.get_type() {
-            VectorType::Vertical => Point { x: 0, y },
-            VectorType::Horizontal => Point { x, y: 0 },
-            // guaranteed to be 45 degree angles
-            VectorType::Diagonal => Point { x, y },
+        let dx = self.point_2.x - self.point_1.x;
+        let dy = self.point_2.y - self.point_1.y;
+        let dz = self.point_2.z - self.point_1.z;
+        let steps = gcd(gcd(dx.abs(), dy.abs()), dz.abs());
+
+        if steps == 0 {
+            return vec![self.point_1];
+        }
+
+        let increase = Point {
+            x: dx / steps,
+            y: dy / steps,
+            z: dz / steps,
         };
 
+        let mut points = vec![self.point_1];
         let mut current = self.point_1;
-        points.push(self.point_1);
-
-        while current != self.point_2 {
+        for _ in 0..steps {
             current = current + increase;
             points.push(current);
         }
@@ -51,33 +56,108 @@ impl Vector {
     }
 
     pub fn get_type(&self) -> VectorType {
-        if self.point_1.x == self.point_2.x {
+        let dx = (self.point_1.x - self.point_2.x).abs();
+        let dy = (self.point_1.y - self.point_2.y).abs();
+        let dz = (self.point_1.z - self.point_2.z).abs();
+
+        if dx == 0 && dz == 0 {
             VectorType::Vertical
-        } else if self.point_1.y == self.point_2.y {
+        } else if dy == 0 && dz == 0 {
             VectorType::Horizontal
-        } else {
+        } else if dx == 0 && dy == 0 {
+            VectorType::Depth
+        } else if [dx, dy, dz]
+            .iter()
+            .all(|d| *d == 0 || *d == dx.max(dy).max(dz))
+        {
             VectorType::Diagonal
+        } else {
+            VectorType::Sloped
         }
     }
+
+    /// Whether both ends share the same z, so the whole vector lies in one plane
+    pub fn is_flat(&self) -> bool {
+        self.point_1.z == self.point_2.z
+    }
+}
+
+pub(crate) fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
 }
 
-impl std::ops::Add for Point {
-    type Output = Self;
+impl std::str::FromStr for VectorType {
+    type Err = VectorParseError;
 
-    fn add(self, other: Self) -> Self {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "horizontal" => Ok(VectorType::Horizontal),
+            "vertical" => Ok(VectorType::Vertical),
+            "depth" => Ok(VectorType::Depth),
+            "diagonal" => Ok(VectorType::Diagonal),
+            "sloped" => Ok(VectorType::Sloped),
+            _ => Err(VectorParseError::UnknownType(value.to_string())),
         }
     }
 }
 
+/// The vectors of the puzzle example
+#[cfg(test)]
+pub(crate) const EXAMPLE: [&str; 10] = [
+    "0,9 -> 5,9",
+    "8,0 -> 0,8",
+    "9,4 -> 3,4",
+    "2,2 -> 2,1",
+    "7,0 -> 7,4",
+    "6,4 -> 2,0",
+    "0,9 -> 2,9",
+    "3,4 -> 1,4",
+    "0,0 -> 8,8",
+    "5,5 -> 8,2",
+];
+
+#[cfg(test)]
+pub(crate) fn parse_all(lines: &[&str]) -> Result<Vec<Vector>, VectorParseError> {
+    lines.iter().map(|l| l.parse()).collect()
+}
+
 #[derive(Debug, Error)]
 pub enum VectorParseError {
     #[error("Missing {0}")]
     MissingValue(String),
     #[error("Invalid integer coordinate")]
     InvalidInt(#[from] std::num::ParseIntError),
+    #[error("Too many coordinates in {0}")]
+    ExtraValue(String),
+    #[error("Unknown vector type: {0}")]
+    UnknownType(String),
+}
+
+impl std::fmt::Display for Point {
+    /// Formats as `x,y`, adding `,z` only when it is set
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.z == 0 {
+            write!(f, "{},{}", self.x, self.y)
+        } else {
+            write!(f, "{},{},{}", self.x, self.y, self.z)
+        }
+    }
+}
+
+impl std::fmt::Display for Vector {
+    /// Formats as `x,y -> x,y`, with z on both ends unless both are 0
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.point_1.z == 0 && self.point_2.z == 0 {
+            write!(f, "{} -> {}", self.point_1, self.point_2)
+        } else {
+            let (a, b) = (self.point_1, self.point_2);
+            write!(f, "{},{},{} -> {},{},{}", a.x, a.y, a.z, b.x, b.y, b.z)
+        }
+    }
 }
 
 impl std::str::FromStr for Vector {
@@ -101,6 +181,7 @@ impl std::str::FromStr for Vector {
 impl std::str::FromStr for Point {
     type Err = VectorParseError;
 
+    /// Parses `x,y` or `x,y,z`
     fn from_str(value: &str) -> Result<Self, Self::Err> {
         let mut tokens = value.split(',');
         let x = tokens
@@ -111,7 +192,106 @@ impl std::str::FromStr for Point {
             .next()
             .ok_or_else(|| VectorParseError::MissingValue("y".to_string()))?
             .parse()?;
+        let z = match tokens.next() {
+            Some(z) => z.parse()?,
+            None => 0,
+        };
+        if tokens.next().is_some() {
+            return Err(VectorParseError::ExtraValue(value.to_string()));
+        }
+
+        Ok(Point { x, y, z })
+    }
+}
+
+#[cfg(test)]
+mod test_vector {
+    use super::*;
+
+    fn get_path(value: &str) -> Result<Vec<(i32, i32)>, VectorParseError> {
+        let vector: Vector = value.parse()?;
+        Ok(vector.get_path().iter().map(|p| (p.x, p.y)).collect())
+    }
+
+    fn get_path_3d(value: &str) -> Result<Vec<(i32, i32, i32)>, VectorParseError> {
+        let vector: Vector = value.parse()?;
+        Ok(vector.get_path().iter().map(|p| (p.x, p.y, p.z)).collect())
+    }
+
+    #[test]
+    fn test_get_path() -> Result<(), VectorParseError> {
+        assert_eq!(get_path("1,1 -> 1,3")?, vec![(1, 1), (1, 2), (1, 3)]);
+        assert_eq!(get_path("9,7 -> 7,7")?, vec![(9, 7), (8, 7), (7, 7)]);
+        assert_eq!(get_path("9,7 -> 7,9")?, vec![(9, 7), (8, 8), (7, 9)]);
+        assert_eq!(
+            get_path("0,0 -> 6,3")?,
+            vec![(0, 0), (2, 1), (4, 2), (6, 3)]
+        );
+        assert_eq!(get_path("0,0 -> 2,5")?, vec![(0, 0), (2, 5)]);
+        assert_eq!(get_path("3,4 -> 3,4")?, vec![(3, 4)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_type() -> Result<(), VectorParseError> {
+        assert_eq!(
+            "0,0 -> 0,5".parse::<Vector>()?.get_type(),
+            VectorType::Vertical
+        );
+        assert_eq!(
+            "0,0 -> 5,0".parse::<Vector>()?.get_type(),
+            VectorType::Horizontal
+        );
+        assert_eq!(
+            "0,5 -> 5,0".parse::<Vector>()?.get_type(),
+            VectorType::Diagonal
+        );
+        assert_eq!(
+            "0,0 -> 6,3".parse::<Vector>()?.get_type(),
+            VectorType::Sloped
+        );
+        assert_eq!(
+            "1,1,0 -> 1,1,4".parse::<Vector>()?.get_type(),
+            VectorType::Depth
+        );
+        assert_eq!(
+            "0,0,0 -> 3,3,3".parse::<Vector>()?.get_type(),
+            VectorType::Diagonal
+        );
+        assert_eq!(
+            "0,0,0 -> 3,0,3".parse::<Vector>()?.get_type(),
+            VectorType::Diagonal
+        );
+        assert_eq!(
+            "0,0,0 -> 3,3,1".parse::<Vector>()?.get_type(),
+            VectorType::Sloped
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_3d() -> Result<(), VectorParseError> {
+        let flat: Vector = "1,2 -> 3,4".parse()?;
+        assert_eq!(flat, "1,2,0 -> 3,4,0".parse()?);
+        assert_eq!(flat.to_string(), "1,2 -> 3,4");
+        assert!(flat.is_flat());
+
+        let vector: Vector = "0,0,6 -> 2,4,0".parse()?;
+        assert_eq!(vector.to_string(), "0,0,6 -> 2,4,0");
+        assert!(!vector.is_flat());
+        assert_eq!(
+            get_path_3d("0,0,6 -> 2,4,0")?,
+            vec![(0, 0, 6), (1, 2, 3), (2, 4, 0)]
+        );
+        assert_eq!(
+            get_path_3d("1,1,1 -> 1,1,3")?,
+            vec![(1, 1, 1), (1, 1, 2), (1, 1, 3)]
+        );
+
+        assert!("1,2,3,4 -> 0,0".parse::<Vector>().is_err());
 
-        Ok(Point { x, y })
+        Ok(())
     }
 }
@@ -0,0 +1,123 @@
+use std::fmt;
+
+/// Each limb holds nine decimal digits, which keeps printing trivial
+const BASE: u64 = 1_000_000_000;
+
+/// Unsigned integer of any size, stored as base 10^9 limbs with the least significant first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> BigUint {
+        BigUint { limbs: vec![] }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn add(&self, other: &BigUint) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = 0;
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let sum = carry
+                + *self.limbs.get(i).unwrap_or(&0) as u64
+                + *other.limbs.get(i).unwrap_or(&0) as u64;
+            limbs.push((sum % BASE) as u32);
+            carry = sum / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry as u32);
+        }
+        BigUint { limbs }
+    }
+
+    pub fn mul(&self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+
+        let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let product = limbs[i + j] + *a as u64 * *b as u64 + carry;
+                limbs[i + j] = product % BASE;
+                carry = product / BASE;
+            }
+            limbs[i + other.limbs.len()] += carry;
+        }
+
+        let mut result = BigUint {
+            limbs: limbs.into_iter().map(|l| l as u32).collect(),
+        };
+        result.trim();
+        result
+    }
+
+    /// Drops leading zero limbs so every number has a single representation
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(mut value: u64) -> BigUint {
+        let mut limbs = vec![];
+        while value > 0 {
+            limbs.push((value % BASE) as u32);
+            value /= BASE;
+        }
+        BigUint { limbs }
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut limbs = self.limbs.iter().rev();
+        match limbs.next() {
+            None => write!(f, "0"),
+            Some(first) => {
+                write!(f, "{}", first)?;
+                for limb in limbs {
+                    write!(f, "{:09}", limb)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_bigint {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = BigUint::from(u64::MAX);
+        let b = BigUint::from(1_000_000_007);
+
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(a.to_string(), u64::MAX.to_string());
+        assert_eq!(a.add(&b).to_string(), "18446744074709551622");
+        assert_eq!(
+            a.mul(&a).to_string(),
+            (u64::MAX as u128 * u64::MAX as u128).to_string()
+        );
+        assert_eq!(
+            b.mul(&BigUint::from(1_000_000_000)).to_string(),
+            "1000000007000000000"
+        );
+        assert_eq!(a.mul(&BigUint::zero()), BigUint::zero());
+        assert_eq!(
+            BigUint::from(999_999_999)
+                .add(&BigUint::from(1))
+                .to_string(),
+            "1000000000"
+        );
+    }
+}
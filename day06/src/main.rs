@@ -1,62 +1,377 @@
+mod bigint;
+mod matrix;
+mod rules;
+mod series;
+
+use bigint::BigUint;
+use matrix::{Matrix, Modular, Ring};
+use rules::Rules;
 use std::error::Error;
 use std::io::Read;
+use std::ops::Range;
+use thiserror::Error;
+
+/// Number of fish in each stage of the `Rules`, the days left until they give birth unless
+/// they have a maximum age
+type LanternFish = Vec<u64>;
+
+/// A count no longer fitting a u64
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+#[error("Fish count overflowed a u64 on day {day}")]
+struct Overflow {
+    day: u64,
+}
 
-#[derive(Debug)]
-struct LanternFishGroup {
-    count: u64,
-    days: u32,
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn run() -> Result<(), Box<dyn Error>> {
     let mut buffer = String::new();
 
     std::io::stdin().read_to_string(&mut buffer)?;
     let buffer = buffer.strip_suffix('\n').ok_or("invalid value")?;
 
-    let mut fish = vec![];
-    for value in buffer.split(',') {
-        let days = value.parse::<u32>()?;
-        fish.push(LanternFishGroup { count: 1, days });
+    let rules = get_rules()?;
+    let mut fish = parse_fish(buffer, &rules)?;
+
+    if let Some(days) = get_arg("--days=") {
+        let days = days.parse()?;
+        match get_arg("--modulo=") {
+            Some(modulus) => {
+                let modulus = modulus.parse()?;
+                if modulus == 0 {
+                    return Err("modulus must be positive".into());
+                }
+                let count = fast_forward(&fish, &rules, days, &Modular::new(1, modulus));
+                println!("Day {}: {} (mod {})", days, count.value, modulus);
+            }
+            None => {
+                let count = fast_forward(&fish, &rules, days, &BigUint::from(1));
+                println!("Day {}: {}", days, count);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(days) = get_arg("--series=") {
+        return query_series(&fish, &rules, days.parse()?);
+    }
+
+    if let Some(days) = get_arg("--simulate=") {
+        let count = simulate(&fish, &rules, days.parse()?, has_flag("--checked"))?;
+        println!("Day {}: {}", days, count);
+        return Ok(());
+    }
+
+    let part_1 = get_arg("--part-1=").map_or(Ok(80), |d| d.parse())?;
+    let part_2 = get_arg("--part-2=").map_or(Ok(256), |d| d.parse())?;
+    if part_2 < part_1 {
+        return Err("part 2 can not end before part 1".into());
+    }
+
+    run_days(&mut fish, &rules, 0..part_1)?;
+    println!(
+        "Part 1: {}",
+        get_fish_count(&fish).ok_or(Overflow { day: part_1 })?
+    );
+
+    // we already ran the days of part 1, don't run them again
+    run_days(&mut fish, &rules, part_1..part_2)?;
+    println!(
+        "Part 2: {}",
+        get_fish_count(&fish).ok_or(Overflow { day: part_2 })?
+    );
+
+    Ok(())
+}
+
+/// Records `days` days and answers `--csv=<path>`, `--json=<path>` (`-` for the terminal),
+/// `--above=<n>` and `--growth` about them, or about the days before a count overflowed.
+fn query_series(fish: &LanternFish, rules: &Rules, days: u64) -> Result<(), Box<dyn Error>> {
+    let (series, overflow) = series::record(fish, rules, days);
+    if let Some(overflow) = overflow {
+        eprintln!("{}, only recorded the days before", overflow);
+    }
+
+    if let Some(path) = get_arg("--csv=") {
+        series::write_csv(&series, rules, &mut *open_output(&path)?)?;
+    }
+    if let Some(path) = get_arg("--json=") {
+        series::write_json(&series, &mut *open_output(&path)?)?;
+    }
+    if let Some(threshold) = get_arg("--above=") {
+        // a total which overflowed is above any threshold
+        let first = series::first_above(&series, threshold.parse()?).or(overflow.map(|o| o.day));
+        match first {
+            Some(day) => println!("First day above {}: {}", threshold, day),
+            None => println!("Never above {} within {} days", threshold, days),
+        }
+    }
+    if has_flag("--growth") {
+        for (day, rate) in series::growth_rates(&series) {
+            match rate {
+                Some(rate) => println!("Day {}: {:.4}", day, rate),
+                None => println!("Day {}: -", day),
+            }
+        }
+        if let Some(mean) = series::mean_growth_rate(&series) {
+            println!("Mean: {:.4}", mean);
+        }
+    }
+
+    Ok(())
+}
+
+fn open_output(path: &str) -> Result<Box<dyn std::io::Write>, Box<dyn Error>> {
+    if path == "-" {
+        Ok(Box::new(std::io::stdout()))
+    } else {
+        Ok(Box::new(std::fs::File::create(path)?))
+    }
+}
+
+fn has_flag(flag: &str) -> bool {
+    std::env::args().any(|a| a == flag)
+}
+
+fn get_arg(prefix: &str) -> Option<String> {
+    std::env::args().find_map(|a| a.strip_prefix(prefix).map(String::from))
+}
+
+/// Rules from the `--rules=<path>` file, overridden by flags such as `--cycle=7`
+fn get_rules() -> Result<Rules, Box<dyn Error>> {
+    let mut rules = Rules::default();
+    if let Some(path) = get_arg("--rules=") {
+        rules.parse(&std::fs::read_to_string(path)?)?;
+    }
+    for key in rules::KEYS {
+        if let Some(value) = get_arg(&format!("--{}=", key)) {
+            rules.set(key, &value)?;
+        }
     }
+    Ok(rules)
+}
 
-    run_days(&mut fish, 80);
-    println!("Part 1: {}", get_fish_count(&fish));
+fn parse_fish(value: &str, rules: &Rules) -> Result<LanternFish, Box<dyn Error>> {
+    let mut fish = vec![0; rules.stages()];
+    for value in value.split(',') {
+        fish[rules.initial_stage(value.parse()?)?] += 1;
+    }
+    Ok(fish)
+}
 
-    // we already ran 80 days, don't run that again
-    run_days(&mut fish, 256 - 80);
-    println!("Part 2: {}", get_fish_count(&fish));
+/// Returns `None` if the total does not fit a u64
+fn get_fish_count(fish: &LanternFish) -> Option<u64> {
+    fish.iter()
+        .try_fold(0u64, |sum, count| sum.checked_add(*count))
+}
 
+/// Runs the days numbered `days`, counting from 0, failing with the first day a count
+/// overflows. The fish are left as they were at the end of the previous day.
+fn run_days(fish: &mut LanternFish, rules: &Rules, days: Range<u64>) -> Result<(), Overflow> {
+    for day in days {
+        run_day(fish, rules).ok_or(Overflow { day: day + 1 })?;
+    }
     Ok(())
 }
 
-fn get_fish_count(fish: &[LanternFishGroup]) -> u64 {
-    let mut count = 0;
-    for group in fish {
-        count += group.count;
+fn run_day(fish: &mut LanternFish, rules: &Rules) -> Option<()> {
+    let mut next = vec![0u64; fish.len()];
+    for (stage, count) in fish.iter().enumerate() {
+        if let Some(next_stage) = rules.next_stage(stage) {
+            next[next_stage] = next[next_stage].checked_add(*count)?;
+        }
+        if rules.gives_birth(stage) {
+            let newborn = &mut next[rules.newborn_stage()];
+            *newborn = newborn.checked_add(count.checked_mul(rules.litter)?)?;
+        }
     }
-    count
+    *fish = next;
+    Some(())
 }
 
-fn run_days(fish: &mut Vec<LanternFishGroup>, days: u32) {
-    for _ in 0..days {
-        run_day(fish);
+fn run_day_exact<T: Ring>(fish: &mut Vec<T>, rules: &Rules) {
+    let litter = fish[0].with_value(rules.litter);
+    let mut next = vec![fish[0].zero(); fish.len()];
+    for (stage, count) in fish.iter().enumerate() {
+        if let Some(next_stage) = rules.next_stage(stage) {
+            next[next_stage] = next[next_stage].add(count);
+        }
+        if rules.gives_birth(stage) {
+            let newborn = rules.newborn_stage();
+            next[newborn] = next[newborn].add(&count.mul(&litter));
+        }
     }
+    *fish = next;
 }
 
-fn run_day(fish: &mut Vec<LanternFishGroup>) {
-    let mut newborn = 0;
-    for group in fish.iter_mut() {
-        if group.days == 0 {
-            group.days = 6;
-            newborn += group.count;
+/// Counts the fish after `days` one day at a time, switching to big integers once a count
+/// no longer fits a u64, or failing then if `checked` is set.
+fn simulate(
+    fish: &LanternFish,
+    rules: &Rules,
+    days: u64,
+    checked: bool,
+) -> Result<BigUint, Overflow> {
+    let mut fish = fish.clone();
+    // the first day left to run once the counts are exact, and what overflowed
+    let (resume, overflow) = match run_days(&mut fish, rules, 0..days) {
+        Ok(()) => match get_fish_count(&fish) {
+            Some(count) => return Ok(BigUint::from(count)),
+            None => (days, Overflow { day: days }),
+        },
+        Err(overflow) => (overflow.day - 1, overflow),
+    };
+    if checked {
+        return Err(overflow);
+    }
+
+    let mut exact: Vec<BigUint> = fish.iter().map(|count| BigUint::from(*count)).collect();
+    for _ in resume..days {
+        run_day_exact(&mut exact, rules);
+    }
+    Ok(exact
+        .iter()
+        .fold(BigUint::zero(), |sum, count| sum.add(count)))
+}
+
+/// One `run_day` as a matrix, mapping the counts of one day onto the next
+fn get_transition<T: Ring>(rules: &Rules, unit: &T) -> Matrix<T> {
+    Matrix::from_fn(rules.stages(), unit, |stage, previous| {
+        let moved = (rules.next_stage(previous) == Some(stage)) as u64;
+        if rules.gives_birth(previous) && stage == rules.newborn_stage() {
+            moved + rules.litter
         } else {
-            group.days -= 1;
+            moved
         }
+    })
+}
+
+/// Counts the fish after `days` by raising the transition to that power, so even 10^12
+/// days take a few dozen matrix multiplications. `unit` is a one of the number type to
+/// count in: modular counts stay small, exact ones grow by a digit every 27 days or so.
+fn fast_forward<T: Ring>(fish: &LanternFish, rules: &Rules, days: u64, unit: &T) -> T {
+    let counts: Vec<T> = fish.iter().map(|count| unit.with_value(*count)).collect();
+
+    get_transition(rules, unit)
+        .pow(days)
+        .apply(&counts)
+        .iter()
+        .fold(unit.zero(), |sum, count| sum.add(count))
+}
+
+#[cfg(test)]
+mod test_fish {
+    use super::*;
+
+    #[test]
+    fn test_example() -> Result<(), Box<dyn Error>> {
+        let rules = Rules::default();
+        let mut fish = parse_fish("3,4,3,1,2", &rules)?;
+        assert_eq!(get_fish_count(&fish), Some(5));
+
+        run_days(&mut fish, &rules, 0..18)?;
+        assert_eq!(get_fish_count(&fish), Some(26));
+        run_days(&mut fish, &rules, 18..80)?;
+        assert_eq!(get_fish_count(&fish), Some(5934));
+        run_days(&mut fish, &rules, 80..256)?;
+        assert_eq!(get_fish_count(&fish), Some(26984457539));
+
+        assert!(parse_fish("3,9", &rules).is_err());
+
+        Ok(())
     }
-    if newborn > 0 {
-        fish.push(LanternFishGroup {
-            count: newborn,
-            days: 8,
+
+    #[test]
+    fn test_fast_forward() -> Result<(), Box<dyn Error>> {
+        let rules = Rules::default();
+        let fish = parse_fish("3,4,3,1,2", &rules)?;
+        let unit = BigUint::from(1);
+
+        for days in [0, 1, 18, 80, 256] {
+            assert_eq!(
+                fast_forward(&fish, &rules, days, &unit),
+                simulate(&fish, &rules, days, true)?
+            );
+        }
+
+        let modular = fast_forward(&fish, &rules, 256, &Modular::new(1, 1_000_000_007));
+        assert_eq!(modular.value, 26984457539 % 1_000_000_007);
+
+        // beyond u64, checked against the counts modulo a prime
+        let exact = fast_forward(&fish, &rules, 1000, &unit).to_string();
+        let modular = fast_forward(&fish, &rules, 1000, &Modular::new(1, 1_000_000_007));
+        let reduced = exact.bytes().fold(0, |sum, digit| {
+            (sum * 10 + (digit - b'0') as u64) % 1_000_000_007
         });
+        assert!(exact.len() > 20);
+        assert_eq!(reduced, modular.value);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_overflow() -> Result<(), Box<dyn Error>> {
+        let rules = Rules::default();
+        let fish = parse_fish("3,4,3,1,2", &rules)?;
+
+        let mut overflowed = fish.clone();
+        let overflow = run_days(&mut overflowed, &rules, 0..1000).unwrap_err();
+        let mut last = fish.clone();
+        run_days(&mut last, &rules, 0..overflow.day - 1)?;
+        assert_eq!(overflowed, last);
+
+        assert_eq!(simulate(&fish, &rules, 1000, true), Err(overflow));
+        for days in [overflow.day - 1, overflow.day, 1000] {
+            assert_eq!(
+                simulate(&fish, &rules, days, false)?,
+                fast_forward(&fish, &rules, days, &BigUint::from(1))
+            );
+        }
+
+        // every bucket fits but their total does not
+        let full = vec![u64::MAX / 4; rules.stages()];
+        assert_eq!(get_fish_count(&full), None);
+        assert_eq!(simulate(&full, &rules, 0, true), Err(Overflow { day: 0 }));
+        assert_eq!(
+            simulate(&full, &rules, 0, false)?,
+            BigUint::from(u64::MAX / 4).mul(&BigUint::from(9))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rules() -> Result<(), Box<dyn Error>> {
+        let mut immortal = Rules::default();
+        immortal.set("max-age", "300")?;
+        let fish = parse_fish("3,4,3,1,2", &Rules::default())?;
+        let aged = parse_fish("3,4,3,1,2", &immortal)?;
+        assert_eq!(
+            simulate(&aged, &immortal, 256, true)?,
+            simulate(&fish, &Rules::default(), 256, true)?
+        );
+
+        let mut rules = Rules::default();
+        rules.parse("cycle=5\ndelay=1\nlitter=3\nmax-age=12")?;
+        let fish = parse_fish("3,4,3,1,2", &rules)?;
+        for days in [0, 1, 10, 50, 200] {
+            assert_eq!(
+                simulate(&fish, &rules, days, false)?,
+                fast_forward(&fish, &rules, days, &BigUint::from(1))
+            );
+        }
+
+        // every fish dies before giving birth
+        rules.set("max-age", "2")?;
+        let fish = parse_fish("5,5,4", &rules)?;
+        assert_eq!(simulate(&fish, &rules, 2, true)?, BigUint::from(2));
+        assert_eq!(simulate(&fish, &rules, 3, true)?, BigUint::zero());
+
+        Ok(())
     }
 }
@@ -0,0 +1,177 @@
+use crate::bigint::BigUint;
+
+/// The numbers a `Matrix` can hold. New values are derived from an existing one so
+/// modular numbers can carry their modulus along.
+pub trait Ring: Clone {
+    fn with_value(&self, value: u64) -> Self;
+    fn add(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+
+    fn zero(&self) -> Self {
+        self.with_value(0)
+    }
+}
+
+/// Integer modulo `modulus`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Modular {
+    pub value: u64,
+    pub modulus: u64,
+}
+
+impl Modular {
+    pub fn new(value: u64, modulus: u64) -> Modular {
+        Modular {
+            value: value % modulus,
+            modulus,
+        }
+    }
+}
+
+impl Ring for Modular {
+    fn with_value(&self, value: u64) -> Self {
+        Modular::new(value, self.modulus)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Modular::new(
+            ((self.value as u128 + other.value as u128) % self.modulus as u128) as u64,
+            self.modulus,
+        )
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        Modular::new(
+            ((self.value as u128 * other.value as u128) % self.modulus as u128) as u64,
+            self.modulus,
+        )
+    }
+}
+
+impl Ring for BigUint {
+    fn with_value(&self, value: u64) -> Self {
+        BigUint::from(value)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        BigUint::add(self, other)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        BigUint::mul(self, other)
+    }
+}
+
+/// Square matrix, `cells[row][column]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<T> {
+    pub cells: Vec<Vec<T>>,
+}
+
+impl<T: Ring> Matrix<T> {
+    /// Builds a `size` by `size` matrix of the number type of `unit`, with each cell set
+    /// to `get_cell(row, column)`.
+    pub fn from_fn<F: Fn(usize, usize) -> u64>(size: usize, unit: &T, get_cell: F) -> Self {
+        let cells = (0..size)
+            .map(|row| {
+                (0..size)
+                    .map(|column| unit.with_value(get_cell(row, column)))
+                    .collect()
+            })
+            .collect();
+        Matrix { cells }
+    }
+
+    pub fn identity(size: usize, unit: &T) -> Self {
+        Matrix::from_fn(size, unit, |row, column| (row == column) as u64)
+    }
+
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn mul(&self, other: &Matrix<T>) -> Matrix<T> {
+        let size = self.size();
+        let cells = (0..size)
+            .map(|row| {
+                (0..size)
+                    .map(|column| {
+                        (0..size).fold(self.cells[row][0].zero(), |sum, k| {
+                            sum.add(&self.cells[row][k].mul(&other.cells[k][column]))
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        Matrix { cells }
+    }
+
+    /// Raises the matrix to `exponent` by repeated squaring, taking a logarithmic number
+    /// of multiplications.
+    pub fn pow(&self, mut exponent: u64) -> Matrix<T> {
+        let mut result = Matrix::identity(self.size(), &self.cells[0][0]);
+        let mut square = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&square);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                square = square.mul(&square);
+            }
+        }
+        result
+    }
+
+    /// Multiplies the matrix with a column vector
+    pub fn apply(&self, vector: &[T]) -> Vec<T> {
+        self.cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(vector)
+                    .fold(vector[0].zero(), |sum, (a, b)| sum.add(&a.mul(b)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_matrix {
+    use super::*;
+
+    /// Matrix stepping the pair (F(n + 1), F(n)) of the Fibonacci sequence
+    fn fibonacci<T: Ring>(unit: &T) -> Matrix<T> {
+        Matrix::from_fn(2, unit, |row, column| (row == 0 || column == 0) as u64)
+    }
+
+    #[test]
+    fn test_pow() {
+        let unit = BigUint::from(1);
+        let matrix = fibonacci(&unit);
+
+        assert_eq!(matrix.pow(0), Matrix::identity(2, &unit));
+        assert_eq!(matrix.pow(10).cells[0][1].to_string(), "55");
+        assert_eq!(
+            matrix.pow(100).cells[0][1].to_string(),
+            "354224848179261915075"
+        );
+
+        let modular = fibonacci(&Modular::new(1, 1_000_000_007)).pow(100);
+        assert_eq!(
+            modular.cells[0][1].value,
+            (354224848179261915075u128 % 1_000_000_007) as u64
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let unit = Modular::new(1, 7);
+        let matrix = fibonacci(&unit);
+        let vector = vec![Modular::new(5, 7), Modular::new(4, 7)];
+
+        let result = matrix.apply(&vector);
+        assert_eq!(result[0].value, 2);
+        assert_eq!(result[1].value, 5);
+    }
+}
@@ -0,0 +1,183 @@
+use std::error::Error;
+
+/// How the fish reproduce. Fish are tracked in stages: the days left until they give birth,
+/// or their age in days when they die at `max_age`, since their timer alone does not tell
+/// how old they are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rules {
+    /// Days between two births of the same fish
+    pub cycle: usize,
+    /// Extra days a newborn fish waits before its first cycle
+    pub delay: usize,
+    /// Fish born to each fish whose timer runs out
+    pub litter: u64,
+    /// Age in days after which a fish dies, fish live forever if `None`
+    pub max_age: Option<usize>,
+}
+
+impl Default for Rules {
+    /// The lanternfish of the puzzle
+    fn default() -> Rules {
+        Rules {
+            cycle: 7,
+            delay: 2,
+            litter: 1,
+            max_age: None,
+        }
+    }
+}
+
+pub const KEYS: [&str; 4] = ["cycle", "delay", "litter", "max-age"];
+
+impl Rules {
+    /// Applies one `key=value` setting, see `KEYS`. A `max-age` of `none` lets fish live
+    /// forever again. The rules are left unchanged if the setting is invalid.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        match key {
+            "cycle" => {
+                let cycle = value.parse()?;
+                if cycle == 0 {
+                    return Err("cycle must be at least 1 day".into());
+                }
+                self.cycle = cycle;
+            }
+            "delay" => self.delay = value.parse()?,
+            "litter" => self.litter = value.parse()?,
+            "max-age" => {
+                self.max_age = match value {
+                    "none" => None,
+                    _ => Some(value.parse()?),
+                }
+            }
+            _ => return Err(format!("Unknown rule: {}", key).into()),
+        }
+        Ok(())
+    }
+
+    /// Applies a rules file of `key=value` lines, skipping blank lines and `#` comments
+    pub fn parse(&mut self, value: &str) -> Result<(), Box<dyn Error>> {
+        for line in value.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value: {}", line))?;
+            self.set(key.trim(), value.trim())?;
+        }
+        Ok(())
+    }
+
+    /// Timer a fish is reset to after giving birth
+    fn reset_timer(&self) -> usize {
+        self.cycle - 1
+    }
+
+    /// Timer newborn fish start with, the longest a fish can have
+    fn newborn_timer(&self) -> usize {
+        self.reset_timer() + self.delay
+    }
+
+    pub fn stages(&self) -> usize {
+        match self.max_age {
+            Some(max_age) => max_age + 1,
+            None => self.newborn_timer() + 1,
+        }
+    }
+
+    /// Stage newborn fish start in
+    pub fn newborn_stage(&self) -> usize {
+        match self.max_age {
+            Some(_) => 0,
+            None => self.newborn_timer(),
+        }
+    }
+
+    /// Stage of a fish of the puzzle input with `timer` days left. Its age is unknown, so
+    /// it is taken to have been born with the newborn timer.
+    pub fn initial_stage(&self, timer: usize) -> Result<usize, Box<dyn Error>> {
+        let newborn = self.newborn_timer();
+        if timer > newborn {
+            return Err(format!("Timer {} above newborn timer {}", timer, newborn).into());
+        }
+
+        let stage = match self.max_age {
+            Some(_) => newborn - timer,
+            None => timer,
+        };
+        if stage >= self.stages() {
+            return Err(format!("Timer {} is past the maximum age", timer).into());
+        }
+        Ok(stage)
+    }
+
+    /// Stage a fish moves to after a day, `None` if it dies
+    pub fn next_stage(&self, stage: usize) -> Option<usize> {
+        match self.max_age {
+            Some(max_age) if stage == max_age => None,
+            Some(_) => Some(stage + 1),
+            None if stage == 0 => Some(self.reset_timer()),
+            None => Some(stage - 1),
+        }
+    }
+
+    /// Whether fish in `stage` give birth at the end of the day
+    pub fn gives_birth(&self, stage: usize) -> bool {
+        self.get_timer(stage) == 0
+    }
+
+    fn get_timer(&self, stage: usize) -> usize {
+        match self.max_age {
+            None => stage,
+            Some(_) => {
+                let newborn = self.newborn_timer();
+                if stage <= newborn {
+                    newborn - stage
+                } else {
+                    self.reset_timer() - (stage - newborn - 1) % self.cycle
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_rules {
+    use super::*;
+
+    #[test]
+    fn test_stages() -> Result<(), Box<dyn Error>> {
+        let rules = Rules::default();
+        assert_eq!(rules.stages(), 9);
+        assert_eq!(rules.newborn_stage(), 8);
+        assert_eq!(rules.next_stage(0), Some(6));
+        assert_eq!(rules.next_stage(8), Some(7));
+        assert!(rules.gives_birth(0));
+        assert!(rules.initial_stage(9).is_err());
+
+        let mut rules = Rules::default();
+        rules.parse("# short lived fish\nmax-age = 20\n\nlitter=2\n")?;
+        assert_eq!(rules.litter, 2);
+        assert_eq!(rules.stages(), 21);
+        assert_eq!(rules.newborn_stage(), 0);
+        assert_eq!(rules.initial_stage(3)?, 5);
+        assert_eq!(rules.next_stage(20), None);
+        let births: Vec<usize> = (0..21).filter(|s| rules.gives_birth(*s)).collect();
+        assert_eq!(births, vec![8, 15]);
+
+        assert!(rules.set("cycle", "0").is_err());
+        assert_eq!(rules.cycle, 7);
+        rules.set("max-age", "none")?;
+        assert_eq!(
+            rules,
+            Rules {
+                litter: 2,
+                ..Rules::default()
+            }
+        );
+        assert!(rules.set("speed", "1").is_err());
+        assert!(rules.parse("cycle").is_err());
+
+        Ok(())
+    }
+}
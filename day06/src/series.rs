@@ -0,0 +1,176 @@
+use crate::rules::Rules;
+use crate::{get_fish_count, run_days, LanternFish, Overflow};
+use std::io::Write;
+
+/// The fish at the end of one day, day 0 being the puzzle input
+#[derive(Debug, Clone, PartialEq)]
+pub struct Day {
+    pub day: u64,
+    pub total: u64,
+    /// Fish in each stage of the rules
+    pub counts: Vec<u64>,
+}
+
+/// Records every day from 0 up to and including `days`. Recording stops at the first day a
+/// count overflows, returning the days before it along with the overflow.
+pub fn record(fish: &LanternFish, rules: &Rules, days: u64) -> (Vec<Day>, Option<Overflow>) {
+    let mut fish = fish.clone();
+    let mut series = vec![];
+
+    for day in 0..=days {
+        if day > 0 {
+            if let Err(overflow) = run_days(&mut fish, rules, day - 1..day) {
+                return (series, Some(overflow));
+            }
+        }
+        let total = match get_fish_count(&fish) {
+            Some(total) => total,
+            None => return (series, Some(Overflow { day })),
+        };
+        series.push(Day {
+            day,
+            total,
+            counts: fish.clone(),
+        });
+    }
+
+    (series, None)
+}
+
+/// First day with more than `threshold` fish, if any
+pub fn first_above(series: &[Day], threshold: u64) -> Option<u64> {
+    series.iter().find(|d| d.total > threshold).map(|d| d.day)
+}
+
+/// Ratio of each day's total to the day before, `None` where the day before had no fish
+pub fn growth_rates(series: &[Day]) -> Vec<(u64, Option<f64>)> {
+    series
+        .windows(2)
+        .map(|pair| {
+            let rate = if pair[0].total == 0 {
+                None
+            } else {
+                Some(pair[1].total as f64 / pair[0].total as f64)
+            };
+            (pair[1].day, rate)
+        })
+        .collect()
+}
+
+/// Average daily growth over the whole series, the geometric mean of `growth_rates`
+pub fn mean_growth_rate(series: &[Day]) -> Option<f64> {
+    let (first, last) = (series.first()?, series.last()?);
+    if first.total == 0 || last.day == first.day {
+        return None;
+    }
+    let days = (last.day - first.day) as f64;
+    Some((last.total as f64 / first.total as f64).powf(1.0 / days))
+}
+
+/// Writes one line per day with the total and then each stage, named `timer_<days left>`
+/// or `age_<days>` when the rules have a maximum age.
+pub fn write_csv(series: &[Day], rules: &Rules, writer: &mut dyn Write) -> std::io::Result<()> {
+    let name = match rules.max_age {
+        Some(_) => "age",
+        None => "timer",
+    };
+    let stages = series.first().map_or(0, |d| d.counts.len());
+    let header: Vec<String> = (0..stages).map(|s| format!("{}_{}", name, s)).collect();
+    writeln!(writer, "day,total,{}", header.join(","))?;
+
+    for day in series {
+        let counts: Vec<String> = day.counts.iter().map(|c| c.to_string()).collect();
+        writeln!(writer, "{},{},{}", day.day, day.total, counts.join(","))?;
+    }
+    Ok(())
+}
+
+/// Writes a JSON array with an object per day, holding the counts of every stage
+pub fn write_json(series: &[Day], writer: &mut dyn Write) -> std::io::Result<()> {
+    writeln!(writer, "[")?;
+    for (index, day) in series.iter().enumerate() {
+        let counts: Vec<String> = day.counts.iter().map(|c| c.to_string()).collect();
+        let separator = if index + 1 < series.len() { "," } else { "" };
+        writeln!(
+            writer,
+            "  {{\"day\": {}, \"total\": {}, \"counts\": [{}]}}{}",
+            day.day,
+            day.total,
+            counts.join(", "),
+            separator
+        )?;
+    }
+    writeln!(writer, "]")
+}
+
+#[cfg(test)]
+mod test_series {
+    use super::*;
+    use crate::parse_fish;
+    use std::error::Error;
+
+    #[test]
+    fn test_record() -> Result<(), Box<dyn Error>> {
+        let rules = Rules::default();
+        let fish = parse_fish("3,4,3,1,2", &rules)?;
+        let (series, overflow) = record(&fish, &rules, 80);
+
+        assert_eq!(overflow, None);
+        assert_eq!(series.len(), 81);
+        assert_eq!(series[0].total, 5);
+        assert_eq!(series[18].total, 26);
+        assert_eq!(series[80].total, 5934);
+        assert_eq!(series[1].counts, vec![1, 1, 2, 1, 0, 0, 0, 0, 0]);
+
+        assert_eq!(first_above(&series, 5), Some(2));
+        assert_eq!(first_above(&series, 5934), None);
+
+        let rates = growth_rates(&series);
+        assert_eq!(rates.len(), 80);
+        assert_eq!(rates[0], (1, Some(1.0)));
+        let mean = mean_growth_rate(&series).ok_or("no growth")?;
+        assert!((mean.powi(80) * 5.0 - 5934.0).abs() < 1e-6);
+
+        let (series, overflow) = record(&fish, &rules, 1000);
+        let overflow = overflow.ok_or("no overflow")?;
+        assert_eq!(series.len() as u64, overflow.day);
+        assert_eq!(first_above(&series, 5), Some(2));
+        assert_eq!(record(&fish, &rules, overflow.day - 1).1, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export() -> Result<(), Box<dyn Error>> {
+        let rules = Rules::default();
+        let fish = parse_fish("1,2", &rules)?;
+        let (series, _) = record(&fish, &rules, 2);
+
+        let mut csv = vec![];
+        write_csv(&series, &rules, &mut csv)?;
+        assert_eq!(
+            String::from_utf8(csv)?,
+            "day,total,timer_0,timer_1,timer_2,timer_3,timer_4,timer_5,timer_6,timer_7,timer_8\n\
+             0,2,0,1,1,0,0,0,0,0,0\n\
+             1,2,1,1,0,0,0,0,0,0,0\n\
+             2,3,1,0,0,0,0,0,1,0,1\n"
+        );
+
+        let mut json = vec![];
+        write_json(&series[..2], &mut json)?;
+        assert_eq!(
+            String::from_utf8(json)?,
+            "[\n  {\"day\": 0, \"total\": 2, \"counts\": [0, 1, 1, 0, 0, 0, 0, 0, 0]},\n  \
+             {\"day\": 1, \"total\": 2, \"counts\": [1, 1, 0, 0, 0, 0, 0, 0, 0]}\n]\n"
+        );
+
+        let mut rules = Rules::default();
+        rules.set("max-age", "10")?;
+        let (series, _) = record(&parse_fish("1,2", &rules)?, &rules, 2);
+        let mut csv = vec![];
+        write_csv(&series, &rules, &mut csv)?;
+        assert!(String::from_utf8(csv)?.starts_with("day,total,age_0,age_1,"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use itertools::sorted;
+use std::error::Error;
+
+/// How the best position for a model can be found, from fastest to slowest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    /// The median minimises the total, the case for costs linear in the distance
+    Median,
+    /// The optimum is within a step of the mean, the case for costs made of a squared
+    /// and a linear term
+    Mean,
+    /// The total cost is convex in the position, so the optimum can be searched for
+    Convex,
+    /// Every position between the outermost crabs is tried
+    Exhaustive,
+}
+
+/// Fuel a crab needs to move a distance
+pub trait CostModel {
+    fn cost(&self, distance: u32) -> u64;
+
+    /// The fastest strategy known to find the optimum of this model
+    fn strategy(&self) -> Strategy;
+}
+
+/// One fuel per step
+#[derive(Debug)]
+pub struct Linear;
+
+/// Each step costs one more than the last
+#[derive(Debug)]
+pub struct Triangular;
+
+/// Square of the distance
+#[derive(Debug)]
+pub struct Quadratic;
+
+/// One fuel per step, but never more than `cap`
+#[derive(Debug)]
+pub struct Capped {
+    pub cap: u64,
+}
+
+/// Costs given per distance, starting at 0. Longer distances keep adding the last step of
+/// the table. Costs never fall with distance, so the best position is always between the
+/// outermost crabs.
+#[derive(Debug)]
+pub struct Table {
+    costs: Vec<u64>,
+}
+
+impl CostModel for Linear {
+    fn cost(&self, distance: u32) -> u64 {
+        distance as u64
+    }
+
+    fn strategy(&self) -> Strategy {
+        Strategy::Median
+    }
+}
+
+impl CostModel for Triangular {
+    fn cost(&self, distance: u32) -> u64 {
+        // arithmetic progression
+        let n = distance as u64;
+        (n * (n + 1)) / 2
+    }
+
+    fn strategy(&self) -> Strategy {
+        Strategy::Mean
+    }
+}
+
+impl CostModel for Quadratic {
+    fn cost(&self, distance: u32) -> u64 {
+        distance as u64 * distance as u64
+    }
+
+    fn strategy(&self) -> Strategy {
+        Strategy::Mean
+    }
+}
+
+impl CostModel for Capped {
+    fn cost(&self, distance: u32) -> u64 {
+        (distance as u64).min(self.cap)
+    }
+
+    fn strategy(&self) -> Strategy {
+        // flat beyond the cap, so a position far from most crabs can still be best
+        Strategy::Exhaustive
+    }
+}
+
+impl Table {
+    pub fn new(costs: Vec<u64>) -> Result<Table, Box<dyn Error>> {
+        if costs.is_empty() {
+            return Err("empty cost table".into());
+        }
+        if costs.windows(2).any(|pair| pair[1] < pair[0]) {
+            return Err("cost table must not fall with distance".into());
+        }
+        Ok(Table { costs })
+    }
+
+    /// Parses costs separated by commas or whitespace
+    pub fn parse(value: &str) -> Result<Table, Box<dyn Error>> {
+        let costs = value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse())
+            .collect::<Result<Vec<u64>, _>>()?;
+        Table::new(costs)
+    }
+
+    fn last_step(&self) -> u64 {
+        match self.costs[..] {
+            [.., before, last] => last - before,
+            _ => 0,
+        }
+    }
+}
+
+impl CostModel for Table {
+    fn cost(&self, distance: u32) -> u64 {
+        match self.costs.get(distance as usize) {
+            Some(cost) => *cost,
+            None => {
+                let beyond = distance as u64 - (self.costs.len() as u64 - 1);
+                self.costs[self.costs.len() - 1] + beyond * self.last_step()
+            }
+        }
+    }
+
+    fn strategy(&self) -> Strategy {
+        // a total of convex costs is convex, which needs steps that never shrink
+        let steps: Vec<u64> = self
+            .costs
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .collect();
+        if steps.windows(2).all(|pair| pair[0] <= pair[1]) {
+            Strategy::Convex
+        } else {
+            Strategy::Exhaustive
+        }
+    }
+}
+
+/// Parses `linear`, `triangular`, `quadratic`, `capped:<cap>` or `table:<costs>`
+pub fn parse_model(value: &str) -> Result<Box<dyn CostModel>, Box<dyn Error>> {
+    match value.split_once(':') {
+        Some(("capped", cap)) => Ok(Box::new(Capped { cap: cap.parse()? })),
+        Some(("table", costs)) => Ok(Box::new(Table::parse(costs)?)),
+        None => match value {
+            "linear" => Ok(Box::new(Linear)),
+            "triangular" => Ok(Box::new(Triangular)),
+            "quadratic" => Ok(Box::new(Quadratic)),
+            _ => Err(format!("Unknown cost model: {}", value).into()),
+        },
+        _ => Err(format!("Unknown cost model: {}", value).into()),
+    }
+}
+
+pub fn total_cost(crabs: &[i32], model: &dyn CostModel, position: i32) -> u64 {
+    crabs
+        .iter()
+        .map(|crab| model.cost((crab - position).unsigned_abs()))
+        .sum()
+}
+
+/// Returns a position where aligning all crabs costs the least and that cost, using the
+/// strategy of the model.
+pub fn optimise(crabs: &[i32], model: &dyn CostModel) -> Option<(i32, u64)> {
+    let min = *crabs.iter().min()?;
+    let max = *crabs.iter().max()?;
+
+    let candidates: Vec<i32> = match model.strategy() {
+        Strategy::Median => vec![median(crabs)],
+        Strategy::Mean => {
+            // a cost of (a·d² + b·d) has its squared part smallest at the mean, and the
+            // linear part can move the optimum by at most half a step from it
+            let sum: i64 = crabs.iter().map(|c| *c as i64).sum();
+            let low = sum.div_euclid(crabs.len() as i64) as i32;
+            vec![low - 1, low, low + 1]
+        }
+        Strategy::Convex => vec![convex_search(crabs, model, min, max)],
+        Strategy::Exhaustive => (min..=max).collect(),
+    };
+
+    candidates
+        .into_iter()
+        .map(|position| (position, total_cost(crabs, model, position)))
+        .min_by_key(|(position, cost)| (*cost, *position))
+}
+
+/// Binary search for the first position where the cost stops falling, which is the lowest
+/// point of a convex cost.
+fn convex_search(crabs: &[i32], model: &dyn CostModel, mut low: i32, mut high: i32) -> i32 {
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if total_cost(crabs, model, middle) <= total_cost(crabs, model, middle + 1) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    low
+}
+
+pub fn median(values: &[i32]) -> i32 {
+    let values = sorted(values).collect::<Vec<&i32>>();
+    *values[values.len() / 2]
+}
+
+#[cfg(test)]
+mod test_cost {
+    use super::*;
+
+    const EXAMPLE: [i32; 10] = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+
+    fn brute_force(crabs: &[i32], model: &dyn CostModel) -> Option<u64> {
+        let max = *crabs.iter().max()?;
+        let min = *crabs.iter().min()?;
+        (min..=max)
+            .map(|position| total_cost(crabs, model, position))
+            .min()
+    }
+
+    #[test]
+    fn test_models() -> Result<(), Box<dyn Error>> {
+        assert_eq!(optimise(&EXAMPLE, &Linear), Some((2, 37)));
+        assert_eq!(optimise(&EXAMPLE, &Triangular), Some((5, 168)));
+        assert_eq!(optimise(&[], &Linear), None);
+
+        let table = Table::parse("0, 1, 3, 6")?;
+        assert_eq!(table.cost(3), 6);
+        assert_eq!(table.cost(5), 12);
+        assert_eq!(table.strategy(), Strategy::Convex);
+        assert_eq!(optimise(&EXAMPLE, &table), Some((3, 91)));
+        assert_eq!(Table::parse("0 5 6 7")?.strategy(), Strategy::Exhaustive);
+        assert!(Table::parse("").is_err());
+        assert!(Table::parse("5,0").is_err());
+        assert!(Table::parse("0,3,2").is_err());
+
+        assert_eq!(parse_model("capped:3")?.cost(10), 3);
+        assert_eq!(parse_model("quadratic")?.strategy(), Strategy::Mean);
+        assert!(parse_model("cubic").is_err());
+        assert!(parse_model("capped:x").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_against_brute_force() -> Result<(), Box<dyn Error>> {
+        // single crabs, negative positions, clusters and outliers
+        let cases: [&[i32]; 7] = [
+            &EXAMPLE,
+            &[42],
+            &[-7, -7],
+            &[-50, -3, 0, 12, 149],
+            &[0, 0, 0, 0, 100],
+            &[-20, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55],
+            &[10, 90, 91, 92, 93, 94, 95, 96, 97, 98],
+        ];
+        let models: Vec<Box<dyn CostModel>> = vec![
+            Box::new(Linear),
+            Box::new(Triangular),
+            Box::new(Quadratic),
+            Box::new(Capped { cap: 20 }),
+            Box::new(Table::parse("0,2,5,9,14")?),
+            Box::new(Table::parse("0,4,5,5,9")?),
+        ];
+
+        for crabs in cases {
+            for model in &models {
+                let (position, cost) = optimise(crabs, model.as_ref()).ok_or("no crabs")?;
+                assert_eq!(
+                    Some(cost),
+                    brute_force(crabs, model.as_ref()),
+                    "{:?}",
+                    crabs
+                );
+                assert_eq!(cost, total_cost(crabs, model.as_ref(), position));
+            }
+        }
+
+        Ok(())
+    }
+}
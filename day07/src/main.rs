@@ -1,4 +1,6 @@
-use itertools::sorted;
+mod cost;
+
+use cost::{CostModel, Linear, Table, Triangular};
 use std::error::Error;
 use std::io::Read;
 
@@ -13,47 +15,64 @@ fn main() -> Result<(), Box<dyn Error>> {
     for value in buffer.split(',') {
         crabs.push(value.parse::<i32>()?);
     }
-    println!("Part 1: {}", calculate_part_1(&crabs));
-    println!("Part 2: {}", calculate_part_2(&crabs)?);
 
-    Ok(())
-}
+    if let Some(model) = get_model()? {
+        let (position, cost) = cost::optimise(&crabs, model.as_ref()).ok_or("empty")?;
+        println!(
+            "Best position: {} costing {} ({:?})",
+            position,
+            cost,
+            model.strategy()
+        );
+        return Ok(());
+    }
 
-fn calculate_part_1(crabs: &[i32]) -> i32 {
-    let med = median(crabs);
-    crabs.iter().map(|x| (med - x).abs()).sum()
-}
+    println!("Part 1: {}", calculate_part_1(&crabs)?);
+    let (_, cost) = calculate_part_2(&crabs)?;
+    println!("Part 2: {}", cost);
 
-fn calculate_part_2(crabs: &[i32]) -> Result<u32, Box<dyn Error>> {
-    let max = *crabs.iter().max().ok_or("empty")?;
-    let min = *crabs.iter().min().ok_or("empty")?;
-    let mut result = u32::MAX;
+    Ok(())
+}
 
-    for value in min..max {
-        let cost = calculate_total_real_fuel_cost(crabs, value);
-        if cost < result {
-            result = cost;
-        }
+/// The model from `--model=<model>`, or the cost table file in `--table=<path>`
+fn get_model() -> Result<Option<Box<dyn CostModel>>, Box<dyn Error>> {
+    if let Some(model) = get_arg("--model=") {
+        return Ok(Some(cost::parse_model(&model)?));
     }
+    match get_arg("--table=") {
+        Some(path) => Ok(Some(Box::new(Table::parse(&std::fs::read_to_string(
+            path,
+        )?)?))),
+        None => Ok(None),
+    }
+}
 
-    Ok(result)
+fn get_arg(prefix: &str) -> Option<String> {
+    std::env::args().find_map(|a| a.strip_prefix(prefix).map(String::from))
 }
 
-fn calculate_total_real_fuel_cost(crabs: &[i32], value: i32) -> u32 {
-    let mut cost = 0;
-    for crab in crabs {
-        cost += real_fuel_cost((crab - value).abs() as u32);
-    }
-    cost
+fn calculate_part_1(crabs: &[i32]) -> Result<u64, Box<dyn Error>> {
+    let (_, cost) = cost::optimise(crabs, &Linear).ok_or("empty")?;
+    Ok(cost)
 }
 
-fn real_fuel_cost(distance: u32) -> u32 {
-    // arithmetic progression
-    let n = distance;
-    (n * (n + 1)) / 2
+/// Returns the position with the lowest real fuel cost, and that cost.
+fn calculate_part_2(crabs: &[i32]) -> Result<(i32, u64), Box<dyn Error>> {
+    Ok(cost::optimise(crabs, &Triangular).ok_or("empty")?)
 }
 
-fn median(values: &[i32]) -> i32 {
-    let values = sorted(values).collect::<Vec<&i32>>();
-    *values[values.len() / 2]
+#[cfg(test)]
+mod test_crabs {
+    use super::*;
+
+    #[test]
+    fn test_example() -> Result<(), Box<dyn Error>> {
+        let crabs = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+
+        assert_eq!(calculate_part_1(&crabs)?, 37);
+        assert_eq!(calculate_part_2(&crabs)?, (5, 168));
+        assert!(calculate_part_2(&[]).is_err());
+
+        Ok(())
+    }
 }